@@ -1,10 +1,10 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use homomorphic::{HomomorphicEncryption, tfhe_uint::TfheU32};
-use tfhe::set_server_key;
+use homomorphic::{HeContext, HomomorphicEncryption, tfhe_uint::TfheU32};
 
 fn bench_he(c: &mut Criterion) {
     let (pk, sk) = TfheU32::keygen().unwrap();
-    set_server_key(sk);
+    let ctx = HeContext::new(sk);
+    let _guard = ctx.enter();
 
     let a: u32 = 2;
     let b: u32 = 8;
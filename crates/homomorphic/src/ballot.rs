@@ -0,0 +1,112 @@
+//! Homomorphic tallying of one-hot ballots.
+
+use crate::HeError;
+use serde::{Deserialize, Serialize};
+use tfhe::prelude::*;
+use tfhe::{FheBool, FheUint8, FheUint32};
+
+/// Encrypted flag that is true iff `entries` is one-hot: every entry is 0 or 1
+/// and exactly one of them is 1.
+///
+/// The sum is taken over 32-bit casts so that out-of-range entries can't wrap
+/// around to a valid-looking total. Needs a server key installed, see
+/// [`crate::HeContext`].
+pub fn is_one_hot(entries: &[FheUint8]) -> FheBool {
+    let mut valid = FheBool::encrypt_trivial(true);
+    let mut sum = FheUint32::encrypt_trivial(0u32);
+    for entry in entries {
+        valid &= entry.le(1u8);
+        sum += FheUint32::cast_from(entry.clone());
+    }
+    valid & sum.eq(1u32)
+}
+
+/// Running encrypted per-candidate totals plus a count of rejected ballots.
+///
+/// Each ballot is checked with [`is_one_hot`] and folded in through an encrypted
+/// select, so a malformed ballot adds zero to every candidate without the server
+/// learning which ballots were rejected; only the final count is revealed.
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedTally {
+    pub totals: Vec<FheUint32>,
+    pub rejected: FheUint32,
+}
+
+impl EncryptedTally {
+    /// Starts an all-zero tally. Needs a server key installed.
+    pub fn new(num_candidates: usize) -> Self {
+        EncryptedTally {
+            totals: vec![FheUint32::encrypt_trivial(0u32); num_candidates],
+            rejected: FheUint32::encrypt_trivial(0u32),
+        }
+    }
+
+    /// Adds `ballot` to the totals if it is one-hot, otherwise to the rejected count.
+    pub fn add_ballot(&mut self, ballot: &[FheUint8]) -> Result<(), HeError> {
+        if ballot.len() != self.totals.len() {
+            return Err(HeError::EvalError(format!(
+                "ballot has {} entries, tally has {} candidates",
+                ballot.len(),
+                self.totals.len()
+            )));
+        }
+
+        let valid = is_one_hot(ballot);
+        let zero = FheUint32::encrypt_trivial(0u32);
+        for (total, entry) in self.totals.iter_mut().zip(ballot) {
+            *total += valid.select(&FheUint32::cast_from(entry.clone()), &zero);
+        }
+        self.rejected += FheUint32::cast_from(!valid);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfhe_uint::{TfheU8, TfheU32};
+    use crate::{HeContext, HomomorphicEncryption};
+
+    #[test]
+    fn test_malformed_ballots_contribute_zero() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let ballots: [[u8; 3]; 5] = [
+            [1, 0, 0],
+            [0, 0, 1],
+            [5, 0, 0], // entry out of range
+            [1, 1, 0], // two candidates
+            [0, 0, 0], // abstention without a candidate
+        ];
+
+        let mut tally = EncryptedTally::new(3);
+        for ballot in &ballots {
+            let cts: Vec<FheUint8> = ballot
+                .iter()
+                .map(|v| TfheU8::encrypt(&client_key, v).unwrap())
+                .collect();
+            tally.add_ballot(&cts).unwrap();
+        }
+
+        let totals: Vec<u32> = tally
+            .totals
+            .iter()
+            .map(|ct| TfheU32::decrypt(&client_key, ct).unwrap())
+            .collect();
+        assert_eq!(totals, vec![1, 0, 1]);
+        assert_eq!(TfheU32::decrypt(&client_key, &tally.rejected).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_wrong_ballot_length_is_an_error() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let mut tally = EncryptedTally::new(3);
+        let ballot = vec![TfheU8::encrypt(&client_key, &1).unwrap()];
+        assert!(tally.add_ballot(&ballot).is_err());
+    }
+}
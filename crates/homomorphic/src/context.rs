@@ -0,0 +1,208 @@
+//! Scoped server-key evaluation context.
+
+use crate::{HeError, HomomorphicEncryption};
+use rayon::prelude::*;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use tfhe::{ServerKey, set_server_key, unset_server_key};
+
+thread_local! {
+    /// Key installed on this thread by the innermost active [`HeContext`], so that
+    /// leaving a nested context can put the outer one back.
+    static ACTIVE: RefCell<Option<ServerKey>> = const { RefCell::new(None) };
+}
+
+/// Owns a `ServerKey` and runs homomorphic evaluation under it.
+///
+/// tfhe keeps the server key in a thread-local, so evaluation silently depends on
+/// whichever key the current thread last saw. `HeContext` makes that explicit: the
+/// key is only installed while inside [`HeContext::run`] / [`HeContext::enter`] and
+/// the previous state is restored on exit, so one process can evaluate for several
+/// elections concurrently, on actix workers or rayon pools, without their keys
+/// leaking into each other.
+///
+/// Cloning is cheap; tfhe server keys are reference counted.
+#[derive(Clone)]
+pub struct HeContext {
+    server_key: ServerKey,
+}
+
+impl HeContext {
+    pub fn new(server_key: ServerKey) -> Self {
+        HeContext { server_key }
+    }
+
+    pub fn server_key(&self) -> &ServerKey {
+        &self.server_key
+    }
+
+    /// Installs this context's key on the current thread until the returned guard
+    /// is dropped.
+    pub fn enter(&self) -> HeContextGuard<'_> {
+        let previous = ACTIVE.replace(Some(self.server_key.clone()));
+        set_server_key(self.server_key.clone());
+        HeContextGuard {
+            previous,
+            _ctx: PhantomData,
+        }
+    }
+
+    /// Runs `f` with this context's key installed on the current thread.
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.enter();
+        f()
+    }
+
+    /// Maps `f` over `items` on the rayon pool, installing this context's key on
+    /// every worker thread that picks up a share of the work.
+    pub fn par_map<T, R>(&self, items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+    {
+        items
+            .par_iter()
+            .map_init(|| self.enter(), |_, item| f(item))
+            .collect()
+    }
+
+    /// Homomorphic addition under this context's key.
+    pub fn add<E: HomomorphicEncryption>(
+        &self,
+        ct1: &E::Ciphertext,
+        ct2: &E::Ciphertext,
+    ) -> Result<E::Ciphertext, HeError> {
+        self.run(|| E::add(ct1, ct2))
+    }
+
+    /// Homomorphic multiplication under this context's key.
+    pub fn mul<E: HomomorphicEncryption>(
+        &self,
+        ct1: &E::Ciphertext,
+        ct2: &E::Ciphertext,
+    ) -> Result<E::Ciphertext, HeError> {
+        self.run(|| E::mul(ct1, ct2))
+    }
+
+    /// Folds `cts` with homomorphic addition, starting from `init`.
+    pub fn sum<E: HomomorphicEncryption>(
+        &self,
+        init: E::Ciphertext,
+        cts: &[E::Ciphertext],
+    ) -> Result<E::Ciphertext, HeError> {
+        self.run(|| cts.iter().try_fold(init, |acc, ct| E::add(&acc, ct)))
+    }
+}
+
+/// Keeps an [`HeContext`] installed on the current thread; see [`HeContext::enter`].
+///
+/// Dropping it restores whatever key the enclosing context had installed, or
+/// leaves the thread without a key.
+pub struct HeContextGuard<'a> {
+    previous: Option<ServerKey>,
+    // Tied to the thread it was created on
+    _ctx: PhantomData<(&'a HeContext, *const ())>,
+}
+
+impl Drop for HeContextGuard<'_> {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        match &previous {
+            Some(key) => set_server_key(key.clone()),
+            None => unset_server_key(),
+        }
+        ACTIVE.set(previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfhe_uint::TfheU32;
+    use std::thread;
+    use tfhe::FheUint32;
+
+    #[test]
+    fn test_context_evaluates_without_global_key() {
+        let (client_key, server_key) = TfheU32::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+
+        let ct_a = TfheU32::encrypt(&client_key, &5).unwrap();
+        let ct_b = TfheU32::encrypt(&client_key, &7).unwrap();
+
+        let ct_sum = ctx.add::<TfheU32>(&ct_a, &ct_b).unwrap();
+        let ct_prod = ctx.mul::<TfheU32>(&ct_a, &ct_b).unwrap();
+
+        assert_eq!(TfheU32::decrypt(&client_key, &ct_sum).unwrap(), 12);
+        assert_eq!(TfheU32::decrypt(&client_key, &ct_prod).unwrap(), 35);
+    }
+
+    #[test]
+    fn test_nested_contexts_restore_outer_key() {
+        let (ck_outer, sk_outer) = TfheU32::keygen().unwrap();
+        let (ck_inner, sk_inner) = TfheU32::keygen().unwrap();
+        let outer = HeContext::new(sk_outer);
+        let inner = HeContext::new(sk_inner);
+
+        let a = TfheU32::encrypt(&ck_outer, &1).unwrap();
+        let b = TfheU32::encrypt(&ck_inner, &2).unwrap();
+
+        let _guard = outer.enter();
+        let doubled_b = inner.run(|| TfheU32::add(&b, &b).unwrap());
+        // Back under the outer key
+        let doubled_a = TfheU32::add(&a, &a).unwrap();
+
+        assert_eq!(TfheU32::decrypt(&ck_inner, &doubled_b).unwrap(), 4);
+        assert_eq!(TfheU32::decrypt(&ck_outer, &doubled_a).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_contexts_do_not_cross_contaminate() {
+        let elections: Vec<_> = (0..2u32)
+            .map(|i| {
+                let (client_key, server_key) = TfheU32::keygen().unwrap();
+                let votes: Vec<FheUint32> = (0..4)
+                    .map(|_| TfheU32::encrypt(&client_key, &(i + 1)).unwrap())
+                    .collect();
+                (client_key, HeContext::new(server_key), votes)
+            })
+            .collect();
+
+        let totals: Vec<FheUint32> = thread::scope(|s| {
+            let handles: Vec<_> = elections
+                .iter()
+                .map(|(_, ctx, votes)| {
+                    s.spawn(move || ctx.sum::<TfheU32>(votes[0].clone(), &votes[1..]).unwrap())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (i, ((client_key, _, _), total)) in elections.iter().zip(&totals).enumerate() {
+            assert_eq!(
+                TfheU32::decrypt(client_key, total).unwrap(),
+                4 * (i as u32 + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_par_map_installs_key_on_rayon_workers() {
+        let (client_key, server_key) = TfheU32::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+
+        let values = [1u32, 2, 3, 4];
+        let cts: Vec<FheUint32> = values
+            .iter()
+            .map(|v| TfheU32::encrypt(&client_key, v).unwrap())
+            .collect();
+
+        let doubled = ctx.par_map(&cts, |ct| TfheU32::add(ct, ct).unwrap());
+
+        let decrypted: Vec<u32> = doubled
+            .iter()
+            .map(|ct| TfheU32::decrypt(&client_key, ct).unwrap())
+            .collect();
+        assert_eq!(decrypted, vec![2, 4, 6, 8]);
+    }
+}
@@ -0,0 +1,333 @@
+//! Threshold custody of secret key material.
+//!
+//! The client key decrypts every ballot, so no single party should hold it. It is
+//! serialized and split with Shamir secret sharing over GF(256), one byte at a
+//! time: any `threshold` of the `num_shares` trustees can rebuild it, fewer learn
+//! nothing about it. Each share carries a digest of the secret so that a wrong or
+//! corrupted share is caught at reassembly instead of yielding a garbage key.
+//!
+//! On its way to a trustee a share is sealed to that trustee's X25519 public key
+//! (an ephemeral key agreement, SHA-256 of the transcript as key, ChaCha20-Poly1305),
+//! so it can pass through the server and the election admin unread.
+
+use crate::HeError;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tfhe::ClientKey;
+
+/// One trustee's share of a secret.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    /// Evaluation point of this share, never 0.
+    pub index: u8,
+    /// Number of shares needed to rebuild the secret.
+    pub threshold: u8,
+    /// SHA-256 of the secret, identical across shares of one split.
+    pub digest: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+impl KeyShare {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, HeError> {
+        bincode::serialize(self).map_err(|e| HeError::SerializationError(e.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HeError> {
+        bincode::deserialize(bytes).map_err(|e| HeError::SerializationError(e.to_string()))
+    }
+
+    /// Seals the share so only the holder of `trustee_public`'s secret key can
+    /// open it. The output is the ephemeral public key followed by the ciphertext.
+    pub fn seal(&self, trustee_public: &[u8; 32]) -> Result<Vec<u8>, HeError> {
+        let mut ephemeral_secret = [0u8; 32];
+        OsRng.fill_bytes(&mut ephemeral_secret);
+        let ephemeral = MontgomeryPoint::mul_base_clamped(ephemeral_secret).to_bytes();
+        let shared = MontgomeryPoint(*trustee_public).mul_clamped(ephemeral_secret);
+
+        // Each sealing key is used once, so a fixed nonce is safe
+        let cipher = sealing_cipher(&ephemeral, trustee_public, &shared)?;
+        let ciphertext = cipher
+            .encrypt(&Nonce::default(), self.to_bytes()?.as_slice())
+            .map_err(|_| HeError::CustodyError("failed to seal share".to_string()))?;
+
+        Ok([ephemeral.as_slice(), &ciphertext].concat())
+    }
+
+    /// Opens a share sealed by [`KeyShare::seal`] with the trustee's secret key.
+    pub fn open(sealed: &[u8], trustee_secret: &[u8; 32]) -> Result<Self, HeError> {
+        if sealed.len() < 32 {
+            return Err(HeError::CustodyError(
+                "sealed share is truncated".to_string(),
+            ));
+        }
+        let (ephemeral, ciphertext) = sealed.split_at(32);
+        let ephemeral: [u8; 32] = ephemeral.try_into().unwrap();
+        let public = MontgomeryPoint::mul_base_clamped(*trustee_secret).to_bytes();
+        let shared = MontgomeryPoint(ephemeral).mul_clamped(*trustee_secret);
+
+        let cipher = sealing_cipher(&ephemeral, &public, &shared)?;
+        let bytes = cipher
+            .decrypt(&Nonce::default(), ciphertext)
+            .map_err(|_| HeError::CustodyError("share is not sealed to this key".to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Fresh trustee key pair for sealed shares, as `(secret, public)`.
+pub fn trustee_keypair() -> ([u8; 32], [u8; 32]) {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    (secret, MontgomeryPoint::mul_base_clamped(secret).to_bytes())
+}
+
+/// AEAD keyed by the X25519 agreement between an ephemeral and a trustee key.
+fn sealing_cipher(
+    ephemeral: &[u8; 32],
+    trustee_public: &[u8; 32],
+    shared: &MontgomeryPoint,
+) -> Result<ChaCha20Poly1305, HeError> {
+    // A low-order public key forces the all-zero agreement
+    if shared.to_bytes() == [0u8; 32] {
+        return Err(HeError::CustodyError(
+            "trustee public key is invalid".to_string(),
+        ));
+    }
+
+    let key = Sha256::new()
+        .chain_update(b"key-share-seal")
+        .chain_update(ephemeral)
+        .chain_update(trustee_public)
+        .chain_update(shared.as_bytes())
+        .finalize();
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+/// Splits `secret` into `num_shares` shares, any `threshold` of which rebuild it.
+pub fn split_secret(
+    secret: &[u8],
+    threshold: u8,
+    num_shares: u8,
+) -> Result<Vec<KeyShare>, HeError> {
+    if threshold == 0 || threshold > num_shares {
+        return Err(HeError::CustodyError(format!(
+            "threshold {} is invalid for {} shares",
+            threshold, num_shares
+        )));
+    }
+
+    let digest: [u8; 32] = Sha256::digest(secret).into();
+    let mut shares: Vec<KeyShare> = (1..=num_shares)
+        .map(|index| KeyShare {
+            index,
+            threshold,
+            digest,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    // One random polynomial of degree threshold - 1 per byte, constant term the byte
+    let mut coeffs = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coeffs[0] = byte;
+        OsRng.fill_bytes(&mut coeffs[1..]);
+        for share in &mut shares {
+            share.data.push(eval_poly(&coeffs, share.index));
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Rebuilds a secret from at least `threshold` shares of the same split.
+pub fn combine_shares(shares: &[KeyShare]) -> Result<Vec<u8>, HeError> {
+    let Some(first) = shares.first() else {
+        return Err(HeError::CustodyError("no shares given".to_string()));
+    };
+    if shares.len() < first.threshold as usize {
+        return Err(HeError::CustodyError(format!(
+            "{} shares given, {} needed",
+            shares.len(),
+            first.threshold
+        )));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0
+            || share.threshold != first.threshold
+            || share.digest != first.digest
+            || share.data.len() != first.data.len()
+        {
+            return Err(HeError::CustodyError(
+                "shares do not belong to the same split".to_string(),
+            ));
+        }
+        if shares[..i].iter().any(|s| s.index == share.index) {
+            return Err(HeError::CustodyError(format!(
+                "share {} given twice",
+                share.index
+            )));
+        }
+    }
+
+    // Lagrange basis at x = 0: l_i = prod_{j != i} x_j / (x_j - x_i); minus is xor
+    let used = &shares[..first.threshold as usize];
+    let basis: Vec<u8> = used
+        .iter()
+        .map(|si| {
+            used.iter()
+                .filter(|sj| sj.index != si.index)
+                .fold(1u8, |acc, sj| {
+                    gf_mul(acc, gf_div(sj.index, sj.index ^ si.index))
+                })
+        })
+        .collect();
+
+    let secret: Vec<u8> = (0..first.data.len())
+        .map(|k| {
+            used.iter()
+                .zip(&basis)
+                .fold(0u8, |acc, (share, &l)| acc ^ gf_mul(share.data[k], l))
+        })
+        .collect();
+
+    let digest: [u8; 32] = Sha256::digest(&secret).into();
+    if digest != first.digest {
+        return Err(HeError::CustodyError(
+            "reassembled secret does not match its digest".to_string(),
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// Serializes `client_key` and splits it among trustees.
+pub fn split_client_key(
+    client_key: &ClientKey,
+    threshold: u8,
+    num_shares: u8,
+) -> Result<Vec<KeyShare>, HeError> {
+    let bytes =
+        bincode::serialize(client_key).map_err(|e| HeError::SerializationError(e.to_string()))?;
+    split_secret(&bytes, threshold, num_shares)
+}
+
+/// Rebuilds a client key split by [`split_client_key`].
+pub fn combine_client_key(shares: &[KeyShare]) -> Result<ClientKey, HeError> {
+    let bytes = combine_shares(shares)?;
+    bincode::deserialize(&bytes).map_err(|e| HeError::SerializationError(e.to_string()))
+}
+
+/// Horner evaluation of `coeffs` (lowest degree first) at `x` in GF(256).
+fn eval_poly(coeffs: &[u8], x: u8) -> u8 {
+    coeffs.iter().rev().fold(0u8, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+/// Multiplication in GF(256) modulo the AES polynomial x^8 + x^4 + x^3 + x + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// `a / b` in GF(256), with `b^-1 = b^254`. `b` must be non-zero.
+fn gf_div(a: u8, b: u8) -> u8 {
+    let mut inverse = 1u8;
+    let mut base = b;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            inverse = gf_mul(inverse, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    gf_mul(a, inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfhe_uint::TfheU8;
+    use crate::{HeContext, HomomorphicEncryption, PublicKeyEncryption};
+
+    #[test]
+    fn test_any_threshold_subset_rebuilds_secret() {
+        let secret = b"serialized key material".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        assert_eq!(combine_shares(&shares[..3]).unwrap(), secret);
+        assert_eq!(combine_shares(&shares[2..]).unwrap(), secret);
+        let picked = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(combine_shares(&picked).unwrap(), secret);
+
+        // Below threshold, duplicated or tampered shares are refused
+        assert!(combine_shares(&shares[..2]).is_err());
+        let twice = [shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert!(combine_shares(&twice).is_err());
+        let mut tampered = shares[..3].to_vec();
+        tampered[1].data[0] ^= 1;
+        assert!(combine_shares(&tampered).is_err());
+
+        assert!(split_secret(&secret, 4, 3).is_err());
+    }
+
+    #[test]
+    fn test_sealed_share_opens_only_for_its_trustee() {
+        let share = split_secret(b"serialized key material", 2, 3)
+            .unwrap()
+            .remove(1);
+        let (secret, public) = trustee_keypair();
+        let (other_secret, _) = trustee_keypair();
+
+        let sealed = share.seal(&public).unwrap();
+        assert_eq!(KeyShare::open(&sealed, &secret).unwrap(), share);
+        assert!(KeyShare::open(&sealed, &other_secret).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(KeyShare::open(&tampered, &secret).is_err());
+
+        // The identity is a low-order point; sealing to it would use a known key
+        assert!(share.seal(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_client_key_custody_roundtrip() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let public_key = TfheU8::public_keygen(&client_key).unwrap();
+
+        let shares: Vec<Vec<u8>> = split_client_key(&client_key, 2, 3)
+            .unwrap()
+            .iter()
+            .map(|s| s.to_bytes().unwrap())
+            .collect();
+        drop(client_key);
+
+        // Tally time: two trustees bring their shares
+        let brought = [
+            KeyShare::from_bytes(&shares[2]).unwrap(),
+            KeyShare::from_bytes(&shares[0]).unwrap(),
+        ];
+        let rebuilt = combine_client_key(&brought).unwrap();
+
+        let list = TfheU8::encrypt_compact(&public_key, &[7u8]).unwrap();
+        let cts = HeContext::new(server_key)
+            .run(|| TfheU8::expand(&list))
+            .unwrap();
+        assert_eq!(TfheU8::decrypt(&rebuilt, &cts[0]).unwrap(), 7);
+    }
+}
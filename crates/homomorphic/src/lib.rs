@@ -1,11 +1,19 @@
 //! Homomorphic Encryption Trait Definition
 
+pub mod ballot;
+pub mod context;
+pub mod custody;
+pub mod public_key;
 pub mod tfhe_bool;
+pub mod tfhe_int;
 pub mod tfhe_string;
 pub mod tfhe_uint;
 
+pub use context::{HeContext, HeContextGuard};
 pub use tfhe::prelude::*;
-pub use tfhe::{ClientKey, ConfigBuilder, ServerKey, generate_keys};
+pub use tfhe::{
+    ClientKey, CompactCiphertextList, CompactPublicKey, ConfigBuilder, ServerKey, generate_keys,
+};
 
 use thiserror::Error;
 
@@ -21,6 +29,10 @@ pub enum HeError {
     EvalError(String),
     #[error("Evaluation error: {0}")]
     UnsupportedOperation(String),
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+    #[error("Key custody error: {0}")]
+    CustodyError(String),
 }
 
 /// HomomorphicEncryption trait
@@ -39,7 +51,31 @@ pub trait HomomorphicEncryption {
     /// DECRYPT uses the SecretKey (client-side)
     fn decrypt(sk: &Self::SecretKey, ct: &Self::Ciphertext) -> Result<Self::Plaintext, HeError>;
 
-    /// Homomorphic operations (server-side). They may require server key to be set globally
+    /// Homomorphic operations (server-side). Run them under an [`HeContext`] so the
+    /// server key they use is explicit; see [`HeContext::run`].
     fn add(ct1: &Self::Ciphertext, ct2: &Self::Ciphertext) -> Result<Self::Ciphertext, HeError>;
     fn mul(ct1: &Self::Ciphertext, ct2: &Self::Ciphertext) -> Result<Self::Ciphertext, HeError>;
 }
+
+/// Public-key encryption companion to [`HomomorphicEncryption`].
+///
+/// Lets anyone holding a published key encrypt, so the secret key never has to
+/// leave its owner. Plaintexts are packed into one compact list for transport,
+/// which the evaluator expands into ordinary ciphertexts before computing on them.
+pub trait PublicKeyEncryption: HomomorphicEncryption {
+    type EncryptionKey; // publishable key (used for encrypt only)
+    type CompactCiphertext;
+
+    /// Derive the publishable encryption key from the SecretKey
+    fn public_keygen(sk: &Self::SecretKey) -> Result<Self::EncryptionKey, HeError>;
+
+    /// ENCRYPT uses only the EncryptionKey (voter/client-side)
+    fn encrypt_compact(
+        pk: &Self::EncryptionKey,
+        pts: &[Self::Plaintext],
+    ) -> Result<Self::CompactCiphertext, HeError>;
+
+    /// Unpack a compact list into ciphertexts (server-side, under an [`HeContext`]).
+    /// Fails if any element is not a `Self::Ciphertext`.
+    fn expand(list: &Self::CompactCiphertext) -> Result<Vec<Self::Ciphertext>, HeError>;
+}
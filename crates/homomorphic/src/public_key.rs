@@ -0,0 +1,95 @@
+//! Serialization helpers for public-key encryption.
+//!
+//! Published keys and voter ciphertext lists cross a trust boundary, so they use
+//! tfhe's versioned safe serialization with a size limit rather than raw bincode.
+
+use crate::HeError;
+use tfhe::safe_serialization::{safe_deserialize, safe_serialize};
+use tfhe::{CompactCiphertextList, CompactPublicKey};
+
+/// Upper bound on the serialized size of a public key or ciphertext list.
+pub const SERIALIZED_SIZE_LIMIT: u64 = 1 << 30;
+
+pub fn serialize_public_key(pk: &CompactPublicKey) -> Result<Vec<u8>, HeError> {
+    let mut bytes = Vec::new();
+    safe_serialize(pk, &mut bytes, SERIALIZED_SIZE_LIMIT)
+        .map_err(|e| HeError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
+
+pub fn deserialize_public_key(bytes: &[u8]) -> Result<CompactPublicKey, HeError> {
+    safe_deserialize(bytes, SERIALIZED_SIZE_LIMIT).map_err(HeError::SerializationError)
+}
+
+pub fn serialize_ciphertext_list(list: &CompactCiphertextList) -> Result<Vec<u8>, HeError> {
+    let mut bytes = Vec::new();
+    safe_serialize(list, &mut bytes, SERIALIZED_SIZE_LIMIT)
+        .map_err(|e| HeError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
+
+pub fn deserialize_ciphertext_list(bytes: &[u8]) -> Result<CompactCiphertextList, HeError> {
+    safe_deserialize(bytes, SERIALIZED_SIZE_LIMIT).map_err(HeError::SerializationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfhe_bool::TfheBool;
+    use crate::tfhe_uint::{TfheU8, TfheU32};
+    use crate::{HeContext, HomomorphicEncryption, PublicKeyEncryption};
+
+    #[test]
+    fn test_public_key_encrypt_expand_decrypt() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let public_key = TfheU8::public_keygen(&client_key).unwrap();
+
+        // Voter side: only the published key is needed
+        let ballot = [0u8, 1, 0];
+        let list = TfheU8::encrypt_compact(&public_key, &ballot).unwrap();
+
+        // Server side
+        let ctx = HeContext::new(server_key);
+        let cts = ctx.run(|| TfheU8::expand(&list)).unwrap();
+
+        let decrypted: Vec<u8> = cts
+            .iter()
+            .map(|ct| TfheU8::decrypt(&client_key, ct).unwrap())
+            .collect();
+        assert_eq!(decrypted, ballot);
+    }
+
+    #[test]
+    fn test_serialized_key_and_list_roundtrip() {
+        let (client_key, server_key) = TfheBool::keygen().unwrap();
+        let public_key = TfheBool::public_keygen(&client_key).unwrap();
+
+        let published = serialize_public_key(&public_key).unwrap();
+        let voter_key = deserialize_public_key(&published).unwrap();
+
+        let list = TfheBool::encrypt_compact(&voter_key, &[true, false]).unwrap();
+        let sent = serialize_ciphertext_list(&list).unwrap();
+        let received = deserialize_ciphertext_list(&sent).unwrap();
+
+        let ctx = HeContext::new(server_key);
+        let cts = ctx.run(|| TfheBool::expand(&received)).unwrap();
+
+        let decrypted: Vec<bool> = cts
+            .iter()
+            .map(|ct| TfheBool::decrypt(&client_key, ct).unwrap())
+            .collect();
+        assert_eq!(decrypted, vec![true, false]);
+    }
+
+    #[test]
+    fn test_expand_rejects_wrong_element_type() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let public_key = TfheU8::public_keygen(&client_key).unwrap();
+
+        let list = TfheU8::encrypt_compact(&public_key, &[1u8]).unwrap();
+
+        let ctx = HeContext::new(server_key);
+        assert!(ctx.run(|| TfheU32::expand(&list)).is_err());
+        assert!(deserialize_ciphertext_list(b"not a ciphertext list").is_err());
+    }
+}
@@ -1,7 +1,10 @@
-use crate::{HeError, HomomorphicEncryption};
+use crate::{HeError, HomomorphicEncryption, PublicKeyEncryption};
 use serde::{Deserialize, Serialize};
 use tfhe::prelude::*;
-use tfhe::{ClientKey, ConfigBuilder, FheBool, ServerKey, generate_keys};
+use tfhe::{
+    ClientKey, CompactCiphertextList, CompactPublicKey, ConfigBuilder, FheBool, ServerKey,
+    generate_keys,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TfheBool;
@@ -40,15 +43,61 @@ impl HomomorphicEncryption for TfheBool {
     }
 }
 
+impl PublicKeyEncryption for TfheBool {
+    type EncryptionKey = CompactPublicKey;
+    type CompactCiphertext = CompactCiphertextList;
+
+    fn public_keygen(sk: &Self::SecretKey) -> Result<Self::EncryptionKey, HeError> {
+        CompactPublicKey::try_new(sk).map_err(|_| HeError::KeyGenError)
+    }
+
+    /// Packs booleans into a compact list using only the public key.
+    fn encrypt_compact(
+        pk: &Self::EncryptionKey,
+        pts: &[Self::Plaintext],
+    ) -> Result<Self::CompactCiphertext, HeError> {
+        Ok(CompactCiphertextList::builder(pk)
+            .extend(pts.iter().copied())
+            .build())
+    }
+
+    /// Expands a compact list back into encrypted booleans.
+    fn expand(list: &Self::CompactCiphertext) -> Result<Vec<Self::Ciphertext>, HeError> {
+        let expander = list
+            .expand()
+            .map_err(|e| HeError::EvalError(e.to_string()))?;
+        (0..expander.len())
+            .map(|i| match expander.get::<FheBool>(i) {
+                Ok(Some(ct)) => Ok(ct),
+                _ => Err(HeError::EvalError(format!("element {i} is not a FheBool"))),
+            })
+            .collect()
+    }
+}
+
+impl TfheBool {
+    /// Homomorphic XOR between two encrypted booleans.
+    pub fn xor(a: &FheBool, b: &FheBool) -> Result<FheBool, HeError> {
+        Ok(a ^ b)
+    }
+
+    /// Trivially encrypts a public boolean. No secret key is involved, so this
+    /// can be used server-side for constants mixed into encrypted computations.
+    pub fn encrypt_trivial(pt: bool) -> FheBool {
+        FheBool::encrypt_trivial(pt)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tfhe::set_server_key;
+    use crate::HeContext;
 
     #[test]
     fn test_encrypt_decrypt_roundtrip() {
         let (client_key, server_key) = TfheBool::keygen().unwrap();
-        set_server_key(server_key);
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let value = true;
         let ct = TfheBool::encrypt(&client_key, &value).unwrap();
@@ -64,33 +113,36 @@ mod tests {
     #[test]
     fn test_and_operation() {
         let (client_key, server_key) = TfheBool::keygen().unwrap();
-        set_server_key(server_key);
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let a = TfheBool::encrypt(&client_key, &true).unwrap();
         let b = TfheBool::encrypt(&client_key, &false).unwrap();
 
         let ct_and = TfheBool::mul(&a, &b).unwrap();
         let dec = TfheBool::decrypt(&client_key, &ct_and).unwrap();
-        assert_eq!(dec, false);
+        assert!(!dec);
     }
 
     #[test]
     fn test_or_operation() {
         let (client_key, server_key) = TfheBool::keygen().unwrap();
-        set_server_key(server_key);
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let a = TfheBool::encrypt(&client_key, &false).unwrap();
         let b = TfheBool::encrypt(&client_key, &true).unwrap();
 
         let ct_or = TfheBool::add(&a, &b).unwrap();
         let dec = TfheBool::decrypt(&client_key, &ct_or).unwrap();
-        assert_eq!(dec, true);
+        assert!(dec);
     }
 
     #[test]
     fn test_chain_operations() {
         let (client_key, server_key) = TfheBool::keygen().unwrap();
-        set_server_key(server_key);
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let a = TfheBool::encrypt(&client_key, &true).unwrap();
         let b = TfheBool::encrypt(&client_key, &false).unwrap();
@@ -101,6 +153,20 @@ mod tests {
         let ct_expr = TfheBool::add(&ct_and, &b).unwrap();
 
         let dec = TfheBool::decrypt(&client_key, &ct_expr).unwrap();
-        assert_eq!(dec, true);
+        assert!(dec);
+    }
+
+    #[test]
+    fn test_xor_with_trivial() {
+        let (client_key, server_key) = TfheBool::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let a = TfheBool::encrypt(&client_key, &true).unwrap();
+        let one = TfheBool::encrypt_trivial(true);
+
+        let ct_xor = TfheBool::xor(&a, &one).unwrap();
+        let dec = TfheBool::decrypt(&client_key, &ct_xor).unwrap();
+        assert!(!dec);
     }
 }
@@ -0,0 +1,81 @@
+use crate::tfhe_uint::tfhe_integer;
+use crate::{HeError, HomomorphicEncryption, PublicKeyEncryption};
+use serde::{Deserialize, Serialize};
+use tfhe::prelude::*;
+use tfhe::{
+    ClientKey, CompactCiphertextList, CompactPublicKey, ConfigBuilder, FheBool, ServerKey,
+    generate_keys,
+};
+use tfhe::{FheInt8, FheInt16, FheInt32, FheInt64, FheInt128};
+
+tfhe_integer!(
+    /// Signed 8-bit TFHE integers.
+    TfheI8, FheInt8, i8
+);
+tfhe_integer!(
+    /// Signed 16-bit TFHE integers.
+    TfheI16, FheInt16, i16
+);
+tfhe_integer!(
+    /// Signed 32-bit TFHE integers.
+    TfheI32, FheInt32, i32
+);
+tfhe_integer!(
+    /// Signed 64-bit TFHE integers.
+    TfheI64, FheInt64, i64
+);
+tfhe_integer!(
+    /// Signed 128-bit TFHE integers.
+    TfheI128, FheInt128, i128
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeContext;
+
+    #[test]
+    fn test_encrypt_decrypt_negative() {
+        let (client_key, server_key) = TfheI32::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let value: i32 = -12345;
+        let ct = TfheI32::encrypt(&client_key, &value).unwrap();
+        let dec = TfheI32::decrypt(&client_key, &ct).unwrap();
+
+        assert_eq!(dec, value);
+    }
+
+    #[test]
+    fn test_sub_and_neg() {
+        let (client_key, server_key) = TfheI16::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let ct_a = TfheI16::encrypt(&client_key, &3).unwrap();
+        let ct_b = TfheI16::encrypt(&client_key, &5).unwrap();
+
+        let ct_diff = TfheI16::sub(&ct_a, &ct_b).unwrap();
+        assert_eq!(TfheI16::decrypt(&client_key, &ct_diff).unwrap(), -2);
+
+        let ct_neg = TfheI16::neg(&ct_diff).unwrap();
+        assert_eq!(TfheI16::decrypt(&client_key, &ct_neg).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_arithmetic_shift_and_comparisons() {
+        let (client_key, server_key) = TfheI8::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let ct_a = TfheI8::encrypt(&client_key, &-64).unwrap();
+        let ct_b = TfheI8::encrypt(&client_key, &1).unwrap();
+
+        let ct_shr = TfheI8::shr(&ct_a, 2).unwrap();
+        assert_eq!(TfheI8::decrypt(&client_key, &ct_shr).unwrap(), -16);
+
+        assert!(TfheI8::lt(&ct_a, &ct_b).unwrap().decrypt(&client_key));
+        assert!(!TfheI8::ge(&ct_a, &ct_b).unwrap().decrypt(&client_key));
+    }
+}
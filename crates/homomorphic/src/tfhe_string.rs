@@ -131,12 +131,13 @@ impl TfheString {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tfhe::set_server_key;
+    use crate::HeContext;
 
     #[test]
     fn test_encrypt_decrypt_string() {
         let (client_key, server_key) = TfheString::keygen().unwrap();
-        set_server_key(server_key.clone());
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let value = String::from("Hello TFHE!");
         let ct = TfheString::encrypt(&client_key, &value).unwrap();
@@ -148,7 +149,8 @@ mod tests {
     #[test]
     fn test_string_eq_ne() {
         let (ck, sk) = TfheString::keygen().unwrap();
-        set_server_key(sk);
+        let ctx = HeContext::new(sk);
+        let _guard = ctx.enter();
 
         let s1 = FheAsciiString::try_encrypt("Zama", &ck).unwrap();
         let s2 = FheAsciiString::try_encrypt("zama", &ck).unwrap();
@@ -167,7 +169,8 @@ mod tests {
     #[test]
     fn test_case_conversion() {
         let (ck, sk) = TfheString::keygen().unwrap();
-        set_server_key(sk);
+        let ctx = HeContext::new(sk);
+        let _guard = ctx.enter();
 
         let s = FheAsciiString::try_encrypt("TfHe123!", &ck).unwrap();
         let lower = TfheString::to_lowercase_enc(&s);
@@ -180,7 +183,8 @@ mod tests {
     #[test]
     fn test_trim_and_strip() {
         let (ck, sk) = TfheString::keygen().unwrap();
-        set_server_key(sk);
+        let ctx = HeContext::new(sk);
+        let _guard = ctx.enter();
 
         let s = FheAsciiString::try_encrypt("   tfhe-rs   zama   ", &ck).unwrap();
 
@@ -1,86 +1,208 @@
-use crate::{HeError, HomomorphicEncryption};
+use crate::{HeError, HomomorphicEncryption, PublicKeyEncryption};
 use serde::{Deserialize, Serialize};
-use tfhe::ClientKey;
 use tfhe::prelude::*;
-use tfhe::{ConfigBuilder, FheUint32, generate_keys};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TfheU32;
-
-impl HomomorphicEncryption for TfheU32 {
-    type SecretKey = tfhe::ClientKey;
-    type PublicKey = tfhe::ServerKey;
-    type Ciphertext = FheUint32;
-    type Plaintext = u32;
-
-    fn keygen() -> Result<(Self::SecretKey, Self::PublicKey), HeError> {
-        let config = ConfigBuilder::default().build();
-        let (client_key, server_key) = generate_keys(config);
-        Ok((client_key, server_key))
-    }
-
-    // encrypt now takes the ClientKey (SecretKey)
-    fn encrypt(sk: &Self::SecretKey, pt: &Self::Plaintext) -> Result<Self::Ciphertext, HeError> {
-        FheUint32::try_encrypt(*pt, sk).map_err(|_| HeError::EncryptError)
-    }
-
-    fn decrypt(sk: &Self::SecretKey, ct: &Self::Ciphertext) -> Result<Self::Plaintext, HeError> {
-        Ok(ct.decrypt(sk))
-    }
-
-    fn add(ct1: &FheUint32, ct2: &FheUint32) -> Result<FheUint32, HeError> {
-        Ok(ct1 + ct2)
-    }
-
-    fn mul(ct1: &FheUint32, ct2: &FheUint32) -> Result<FheUint32, HeError> {
-        Ok(ct1 * ct2)
-    }
+use tfhe::{
+    ClientKey, CompactCiphertextList, CompactPublicKey, ConfigBuilder, FheBool, ServerKey,
+    generate_keys,
+};
+use tfhe::{FheUint8, FheUint16, FheUint32, FheUint64, FheUint128};
+
+/// Generates a TFHE integer backend: a marker type implementing
+/// [`HomomorphicEncryption`] over `$fhe`, plus the bitwise, shift, rotate and
+/// comparison helpers used by transciphering and tallying.
+///
+/// Arithmetic wraps modulo 2^bits, matching the clear `$clear` wrapping ops.
+/// Expects `tfhe::prelude::*` and the key/config items imported above to be in
+/// scope at the call site.
+macro_rules! tfhe_integer {
+    ($(#[$meta:meta])* $name:ident, $fhe:ty, $clear:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct $name;
+
+        impl HomomorphicEncryption for $name {
+            type SecretKey = ClientKey;
+            type PublicKey = ServerKey;
+            type Ciphertext = $fhe;
+            type Plaintext = $clear;
+
+            fn keygen() -> Result<(Self::SecretKey, Self::PublicKey), HeError> {
+                let config = ConfigBuilder::default().build();
+                let (client_key, server_key) = generate_keys(config);
+                Ok((client_key, server_key))
+            }
+
+            // encrypt now takes the ClientKey (SecretKey)
+            fn encrypt(
+                sk: &Self::SecretKey,
+                pt: &Self::Plaintext,
+            ) -> Result<Self::Ciphertext, HeError> {
+                <$fhe>::try_encrypt(*pt, sk).map_err(|_| HeError::EncryptError)
+            }
+
+            fn decrypt(
+                sk: &Self::SecretKey,
+                ct: &Self::Ciphertext,
+            ) -> Result<Self::Plaintext, HeError> {
+                Ok(ct.decrypt(sk))
+            }
+
+            fn add(ct1: &$fhe, ct2: &$fhe) -> Result<$fhe, HeError> {
+                Ok(ct1 + ct2)
+            }
+
+            fn mul(ct1: &$fhe, ct2: &$fhe) -> Result<$fhe, HeError> {
+                Ok(ct1 * ct2)
+            }
+        }
+
+        impl PublicKeyEncryption for $name {
+            type EncryptionKey = CompactPublicKey;
+            type CompactCiphertext = CompactCiphertextList;
+
+            fn public_keygen(sk: &Self::SecretKey) -> Result<Self::EncryptionKey, HeError> {
+                CompactPublicKey::try_new(sk).map_err(|_| HeError::KeyGenError)
+            }
+
+            fn encrypt_compact(
+                pk: &Self::EncryptionKey,
+                pts: &[Self::Plaintext],
+            ) -> Result<Self::CompactCiphertext, HeError> {
+                Ok(CompactCiphertextList::builder(pk)
+                    .extend(pts.iter().copied())
+                    .build())
+            }
+
+            fn expand(list: &Self::CompactCiphertext) -> Result<Vec<Self::Ciphertext>, HeError> {
+                let expander = list
+                    .expand()
+                    .map_err(|e| HeError::EvalError(e.to_string()))?;
+                (0..expander.len())
+                    .map(|i| match expander.get::<$fhe>(i) {
+                        Ok(Some(ct)) => Ok(ct),
+                        _ => Err(HeError::EvalError(format!(
+                            "element {i} is not a {}",
+                            stringify!($fhe)
+                        ))),
+                    })
+                    .collect()
+            }
+        }
+
+        impl $name {
+            pub fn add(a: &$fhe, b: &$fhe) -> Result<$fhe, HeError> {
+                // Homomorphic addition (wrapping)
+                Ok(a + b)
+            }
+
+            pub fn sub(a: &$fhe, b: &$fhe) -> Result<$fhe, HeError> {
+                // Homomorphic subtraction (wrapping)
+                Ok(a - b)
+            }
+
+            pub fn neg(a: &$fhe) -> Result<$fhe, HeError> {
+                Ok(-a)
+            }
+
+            /// Trivially encrypts a public value. No secret key is involved, so this
+            /// can be used server-side for constants mixed into encrypted computations.
+            pub fn encrypt_trivial(value: $clear) -> $fhe {
+                <$fhe>::encrypt_trivial(value)
+            }
+
+            pub fn xor(a: &$fhe, b: &$fhe) -> Result<$fhe, HeError> {
+                // tfhe-rs implements bitwise operations natively:
+                Ok(a ^ b)
+            }
+
+            pub fn and(a: &$fhe, b: &$fhe) -> Result<$fhe, HeError> {
+                Ok(a & b)
+            }
+
+            pub fn or(a: &$fhe, b: &$fhe) -> Result<$fhe, HeError> {
+                Ok(a | b)
+            }
+
+            pub fn rotl(a: &$fhe, n: u32) -> Result<$fhe, HeError> {
+                // tfhe-rs supports rotate-left and rotate-right directly
+                Ok(a.rotate_left(n))
+            }
+
+            pub fn rotr(a: &$fhe, n: u32) -> Result<$fhe, HeError> {
+                Ok(a.rotate_right(n))
+            }
+
+            pub fn shl(a: &$fhe, n: u32) -> Result<$fhe, HeError> {
+                Ok(a << n)
+            }
+
+            /// Right shift; arithmetic (sign-extending) for signed types.
+            pub fn shr(a: &$fhe, n: u32) -> Result<$fhe, HeError> {
+                Ok(a >> n)
+            }
+
+            pub fn eq(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.eq(b))
+            }
+
+            pub fn ne(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.ne(b))
+            }
+
+            pub fn lt(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.lt(b))
+            }
+
+            pub fn le(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.le(b))
+            }
+
+            pub fn gt(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.gt(b))
+            }
+
+            pub fn ge(a: &$fhe, b: &$fhe) -> Result<FheBool, HeError> {
+                Ok(a.ge(b))
+            }
+        }
+    };
 }
 
-impl TfheU32 {
-    pub fn add(a: &FheUint32, b: &FheUint32) -> Result<FheUint32, HeError> {
-        // Homomorphic addition (mod 2^32)
-        Ok(a + b)
-    }
-
-    pub fn encrypt_public_with_client(ck: &ClientKey, value: u32) -> Result<FheUint32, HeError> {
-        FheUint32::try_encrypt(value, ck).map_err(|_| HeError::EncryptError)
-    }
-
-    pub fn xor(a: &FheUint32, b: &FheUint32) -> Result<FheUint32, HeError> {
-        // tfhe-rs implements bitwise operations natively:
-        Ok(a ^ b)
-    }
-
-    pub fn rotl(a: &FheUint32, n: u32) -> Result<FheUint32, HeError> {
-        // tfhe-rs supports rotate-left and rotate-right directly on FheUint32
-        Ok(a.rotate_left(n))
-    }
-
-    pub fn rotr(a: &FheUint32, n: u32) -> Result<FheUint32, HeError> {
-        Ok(a.rotate_right(n))
-    }
-
-    pub fn and(a: &FheUint32, b: &FheUint32) -> Result<FheUint32, HeError> {
-        Ok(a & b)
-    }
-
-    pub fn or(a: &FheUint32, b: &FheUint32) -> Result<FheUint32, HeError> {
-        Ok(a | b)
-    }
-}
+pub(crate) use tfhe_integer;
+
+tfhe_integer!(
+    /// Unsigned 8-bit TFHE integers.
+    TfheU8, FheUint8, u8
+);
+tfhe_integer!(
+    /// Unsigned 16-bit TFHE integers.
+    TfheU16, FheUint16, u16
+);
+tfhe_integer!(
+    /// Unsigned 32-bit TFHE integers.
+    TfheU32, FheUint32, u32
+);
+tfhe_integer!(
+    /// Unsigned 64-bit TFHE integers.
+    TfheU64, FheUint64, u64
+);
+tfhe_integer!(
+    /// Unsigned 128-bit TFHE integers.
+    TfheU128, FheUint128, u128
+);
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tfhe::set_server_key;
+    use crate::HeContext;
 
     #[test]
     fn test_encrypt_decrypt_roundtrip() {
         let (client_key, server_key) = TfheU32::keygen().unwrap();
 
-        // Server-side operations require the server key to be set globally:
-        set_server_key(server_key.clone());
+        // Server-side operations run under an explicit evaluation context:
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let value: u32 = 12345;
         let ct = TfheU32::encrypt(&client_key, &value).unwrap();
@@ -92,7 +214,8 @@ mod tests {
     #[test]
     fn test_addition() {
         let (client_key, server_key) = TfheU32::keygen().unwrap();
-        set_server_key(server_key.clone());
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let a: u32 = 5;
         let b: u32 = 7;
@@ -108,7 +231,8 @@ mod tests {
     #[test]
     fn test_multiplication() {
         let (client_key, server_key) = TfheU32::keygen().unwrap();
-        set_server_key(server_key.clone());
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let a: u32 = 2;
         let b: u32 = 3;
@@ -125,7 +249,8 @@ mod tests {
     #[test]
     fn test_chain_operations() {
         let (client_key, server_key) = TfheU32::keygen().unwrap();
-        set_server_key(server_key.clone());
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let x: u32 = 9;
         let y: u32 = 4;
@@ -143,4 +268,74 @@ mod tests {
 
         assert_eq!(dec, (x + y) * z);
     }
+
+    #[test]
+    fn test_u8_wrapping_sub_and_neg() {
+        let (client_key, server_key) = TfheU8::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let ct_a = TfheU8::encrypt(&client_key, &3).unwrap();
+        let ct_b = TfheU8::encrypt(&client_key, &5).unwrap();
+
+        let ct_diff = TfheU8::sub(&ct_a, &ct_b).unwrap();
+        assert_eq!(
+            TfheU8::decrypt(&client_key, &ct_diff).unwrap(),
+            3u8.wrapping_sub(5)
+        );
+
+        let ct_neg = TfheU8::neg(&ct_a).unwrap();
+        assert_eq!(
+            TfheU8::decrypt(&client_key, &ct_neg).unwrap(),
+            3u8.wrapping_neg()
+        );
+    }
+
+    #[test]
+    fn test_u16_shifts_and_rotations() {
+        let (client_key, server_key) = TfheU16::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let x: u16 = 0x8421;
+        let ct = TfheU16::encrypt(&client_key, &x).unwrap();
+
+        let shl = TfheU16::shl(&ct, 3).unwrap();
+        let shr = TfheU16::shr(&ct, 3).unwrap();
+        let rotl = TfheU16::rotl(&ct, 3).unwrap();
+        let rotr = TfheU16::rotr(&ct, 3).unwrap();
+
+        assert_eq!(TfheU16::decrypt(&client_key, &shl).unwrap(), x << 3);
+        assert_eq!(TfheU16::decrypt(&client_key, &shr).unwrap(), x >> 3);
+        assert_eq!(
+            TfheU16::decrypt(&client_key, &rotl).unwrap(),
+            x.rotate_left(3)
+        );
+        assert_eq!(
+            TfheU16::decrypt(&client_key, &rotr).unwrap(),
+            x.rotate_right(3)
+        );
+    }
+
+    #[test]
+    fn test_u64_comparisons() {
+        let (client_key, server_key) = TfheU64::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let ct_a = TfheU64::encrypt(&client_key, &300).unwrap();
+        let ct_b = TfheU64::encrypt(&client_key, &(1 << 40)).unwrap();
+
+        let checks = [
+            (TfheU64::eq(&ct_a, &ct_b).unwrap(), false),
+            (TfheU64::ne(&ct_a, &ct_b).unwrap(), true),
+            (TfheU64::lt(&ct_a, &ct_b).unwrap(), true),
+            (TfheU64::le(&ct_a, &ct_a).unwrap(), true),
+            (TfheU64::gt(&ct_a, &ct_b).unwrap(), false),
+            (TfheU64::ge(&ct_b, &ct_a).unwrap(), true),
+        ];
+        for (ct, expected) in checks {
+            assert_eq!(ct.decrypt(&client_key), expected);
+        }
+    }
 }
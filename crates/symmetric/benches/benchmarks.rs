@@ -1,6 +1,8 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 use symmetric::{
-    SymmetricCipher, aes::AesCipher, camellia::CamelliaCipher, chacha::ChaCha20Cipher,
+    AeadCipher, SymmetricCipher, aes::AesCipher, camellia::CamelliaCipher, chacha::ChaCha20Cipher,
+    chacha_poly1305::ChaCha20Poly1305Cipher, gcm::AesGcmCipher, kreyvium::KreyviumCipher,
+    trivium::TriviumCipher,
 };
 
 fn bench_symmetric(c: &mut Criterion) {
@@ -28,6 +30,36 @@ fn bench_symmetric(c: &mut Criterion) {
             let _ = CamelliaCipher::encrypt(&key_camellia, &iv, &mut plaintext).unwrap();
         })
     });
+
+    let key_chacha_poly = ChaCha20Poly1305Cipher::keygen();
+    c.bench_function("ChaCha20-Poly1305 encrypt 1KB", |b| {
+        b.iter(|| {
+            let _ = ChaCha20Poly1305Cipher::encrypt(&key_chacha_poly, &iv_cha, &plaintext, b"")
+                .unwrap();
+        })
+    });
+
+    let key_gcm = AesGcmCipher::keygen();
+    c.bench_function("AES-256-GCM encrypt 1KB", |b| {
+        b.iter(|| {
+            let _ = AesGcmCipher::encrypt(&key_gcm, &iv_cha, &plaintext, b"").unwrap();
+        })
+    });
+
+    let key_trivium = TriviumCipher::keygen();
+    let iv_trivium = [0u8; 10];
+    c.bench_function("Trivium encrypt 1KB", |b| {
+        b.iter(|| {
+            let _ = TriviumCipher::encrypt(&key_trivium, &iv_trivium, &mut plaintext).unwrap();
+        })
+    });
+
+    let key_kreyvium = KreyviumCipher::keygen();
+    c.bench_function("Kreyvium encrypt 1KB", |b| {
+        b.iter(|| {
+            let _ = KreyviumCipher::encrypt(&key_kreyvium, &iv, &mut plaintext).unwrap();
+        })
+    });
 }
 
 criterion_group!(benches, bench_symmetric);
@@ -0,0 +1,225 @@
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use rand::RngCore;
+
+use crate::{AeadCipher, SymmetricError};
+
+/// ChaCha20-Poly1305 authenticated encryption (RFC 8439).
+///
+/// Combines the [`ChaCha20Cipher`](crate::chacha::ChaCha20Cipher) keystream with a
+/// Poly1305 one-time authenticator. The 16-byte tag covers both the ciphertext and
+/// the associated data, so tampering with either is detected on decryption.
+///
+/// # Features
+///
+/// - **Key size**: 256 bits (32 bytes)
+/// - **Nonce size**: 96 bits (12 bytes)
+/// - **Tag size**: 128 bits (16 bytes), appended to the ciphertext
+///
+/// # Security
+///
+/// As with plain ChaCha20, a nonce must never be reused under the same key; doing
+/// so leaks the XOR of plaintexts and allows tag forgeries. With random nonces,
+/// prefer [`XChaCha20Poly1305Cipher`] which has a nonce large enough to be drawn
+/// at random safely.
+pub struct ChaCha20Poly1305Cipher;
+
+impl AeadCipher for ChaCha20Poly1305Cipher {
+    type Key = [u8; 32]; // 256-bit key
+    type Nonce = [u8; 12]; // 96-bit nonce
+
+    fn keygen() -> Self::Key {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = ChaCha20Poly1305::new(key.into());
+        cipher
+            .encrypt(
+                nonce.into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::EncryptError)
+    }
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = ChaCha20Poly1305::new(key.into());
+        cipher
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::AuthenticationFailed)
+    }
+}
+
+/// XChaCha20-Poly1305 authenticated encryption.
+///
+/// Same construction as [`ChaCha20Poly1305Cipher`], but the 192-bit nonce is first
+/// run through HChaCha20 to derive a subkey. The extended nonce makes it safe to
+/// generate nonces at random for every message.
+///
+/// # Features
+///
+/// - **Key size**: 256 bits (32 bytes)
+/// - **Nonce size**: 192 bits (24 bytes)
+/// - **Tag size**: 128 bits (16 bytes), appended to the ciphertext
+pub struct XChaCha20Poly1305Cipher;
+
+impl AeadCipher for XChaCha20Poly1305Cipher {
+    type Key = [u8; 32]; // 256-bit key
+    type Nonce = [u8; 24]; // 192-bit extended nonce
+
+    fn keygen() -> Self::Key {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = XChaCha20Poly1305::new(key.into());
+        cipher
+            .encrypt(
+                nonce.into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::EncryptError)
+    }
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = XChaCha20Poly1305::new(key.into());
+        cipher
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::AuthenticationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    fn rfc_key() -> [u8; 32] {
+        hex::decode("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rfc8439_aead_vector() {
+        // RFC 8439, section 2.8.2
+        let key = rfc_key();
+        let nonce: [u8; 12] = hex::decode("070000004041424344454647")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+
+        let expected_ct = hex::decode(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+             3ff4def08e4b7a9de576d26586cec64b6116",
+        )
+        .unwrap();
+        let expected_tag = hex::decode("1ae10b594f09e26a7e902ecbd0600691").unwrap();
+
+        let out = ChaCha20Poly1305Cipher::encrypt(&key, &nonce, SUNSCREEN, &aad).unwrap();
+        let (ct, tag) = out.split_at(out.len() - 16);
+        assert_eq!(ct, expected_ct.as_slice());
+        assert_eq!(tag, expected_tag.as_slice());
+
+        let decrypted = ChaCha20Poly1305Cipher::decrypt(&key, &nonce, &out, &aad).unwrap();
+        assert_eq!(decrypted, SUNSCREEN);
+    }
+
+    #[test]
+    fn test_xchacha_draft_vector() {
+        // draft-irtf-cfrg-xchacha, appendix A.3.1
+        let key = rfc_key();
+        let nonce: [u8; 24] = hex::decode("404142434445464748494a4b4c4d4e4f5051525354555657")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+
+        let expected_ct = hex::decode(
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb\
+             731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452\
+             2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9\
+             21f9664c97637da9768812f615c68b13b52e",
+        )
+        .unwrap();
+        let expected_tag = hex::decode("c0875924c1c7987947deafd8780acf49").unwrap();
+
+        let out = XChaCha20Poly1305Cipher::encrypt(&key, &nonce, SUNSCREEN, &aad).unwrap();
+        let (ct, tag) = out.split_at(out.len() - 16);
+        assert_eq!(ct, expected_ct.as_slice());
+        assert_eq!(tag, expected_tag.as_slice());
+
+        let decrypted = XChaCha20Poly1305Cipher::decrypt(&key, &nonce, &out, &aad).unwrap();
+        assert_eq!(decrypted, SUNSCREEN);
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_is_rejected() {
+        let key = ChaCha20Poly1305Cipher::keygen();
+        let nonce: [u8; 12] = rand::random();
+
+        let mut ct = ChaCha20Poly1305Cipher::encrypt(&key, &nonce, b"ballot", b"").unwrap();
+        ct[0] ^= 1;
+
+        let result = ChaCha20Poly1305Cipher::decrypt(&key, &nonce, &ct, b"");
+        assert!(matches!(result, Err(SymmetricError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_wrong_aad_is_rejected() {
+        let key = XChaCha20Poly1305Cipher::keygen();
+        let nonce: [u8; 24] = rand::random();
+
+        let ct = XChaCha20Poly1305Cipher::encrypt(&key, &nonce, b"ballot", b"election-1").unwrap();
+
+        let result = XChaCha20Poly1305Cipher::decrypt(&key, &nonce, &ct, b"election-2");
+        assert!(matches!(result, Err(SymmetricError::AuthenticationFailed)));
+    }
+}
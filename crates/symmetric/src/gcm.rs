@@ -0,0 +1,144 @@
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use rand::RngCore;
+
+use crate::{AeadCipher, SymmetricError};
+
+/// AES-256-GCM authenticated encryption (NIST SP 800-38D).
+///
+/// GCM runs AES in counter mode for confidentiality and authenticates the
+/// ciphertext together with the associated data using the GHASH universal hash.
+/// The 16-byte tag is appended to the ciphertext.
+///
+/// # Features
+///
+/// - **Key size**: 256 bits (32 bytes)
+/// - **Nonce size**: 96 bits (12 bytes)
+/// - **Tag size**: 128 bits (16 bytes), appended to the ciphertext
+///
+/// # Security
+///
+/// Reusing a nonce under the same key is catastrophic for GCM: beyond leaking
+/// plaintext relations it reveals the authentication subkey. Random 96-bit nonces
+/// should be limited to about 2^32 messages per key.
+pub struct AesGcmCipher;
+
+impl AeadCipher for AesGcmCipher {
+    type Key = [u8; 32]; // 256-bit key
+    type Nonce = [u8; 12]; // 96-bit nonce
+
+    fn keygen() -> Self::Key {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = Aes256Gcm::new(key.into());
+        cipher
+            .encrypt(
+                nonce.into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::EncryptError)
+    }
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let cipher = Aes256Gcm::new(key.into());
+        cipher
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| SymmetricError::AuthenticationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcm_spec_zero_vector() {
+        // GCM spec (McGrew & Viega), test case 14
+        let key = [0u8; 32];
+        let nonce = [0u8; 12];
+        let plaintext = [0u8; 16];
+
+        let out = AesGcmCipher::encrypt(&key, &nonce, &plaintext, b"").unwrap();
+        assert_eq!(
+            hex::encode(&out),
+            "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919"
+        );
+    }
+
+    #[test]
+    fn test_gcm_spec_vector_with_aad() {
+        // GCM spec (McGrew & Viega), test case 16
+        let key: [u8; 32] =
+            hex::decode("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let nonce: [u8; 12] = hex::decode("cafebabefacedbaddecaf888")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let plaintext = hex::decode(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        )
+        .unwrap();
+        let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2").unwrap();
+
+        let out = AesGcmCipher::encrypt(&key, &nonce, &plaintext, &aad).unwrap();
+        let (ct, tag) = out.split_at(out.len() - 16);
+        assert_eq!(
+            hex::encode(ct),
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+             8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+        );
+        assert_eq!(hex::encode(tag), "76fc6ece0f4e1768cddf8853bb2d551b");
+
+        let decrypted = AesGcmCipher::decrypt(&key, &nonce, &out, &aad).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_gcm_tampered_tag_is_rejected() {
+        let key = AesGcmCipher::keygen();
+        let nonce: [u8; 12] = rand::random();
+
+        let mut ct = AesGcmCipher::encrypt(&key, &nonce, b"payload", b"header").unwrap();
+        let last = ct.len() - 1;
+        ct[last] ^= 0x80;
+
+        let result = AesGcmCipher::decrypt(&key, &nonce, &ct, b"header");
+        assert!(matches!(result, Err(SymmetricError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_gcm_truncated_ciphertext_is_rejected() {
+        let key = AesGcmCipher::keygen();
+        let nonce: [u8; 12] = rand::random();
+
+        let result = AesGcmCipher::decrypt(&key, &nonce, &[0u8; 8], b"");
+        assert!(matches!(result, Err(SymmetricError::AuthenticationFailed)));
+    }
+}
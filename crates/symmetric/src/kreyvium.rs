@@ -0,0 +1,177 @@
+use rand::RngCore;
+
+use crate::trivium::{WARMUP_ROUNDS, bits_from_bytes};
+use crate::{SymmetricCipher, SymmetricError};
+
+/// Kreyvium symmetric stream cipher.
+///
+/// Kreyvium (Canteaut et al., FSE 2016) is a variant of
+/// [`TriviumCipher`](crate::trivium::TriviumCipher) with a 128-bit security level,
+/// designed specifically for transciphering. It keeps Trivium's 288-bit state and
+/// round function and adds two 128-bit rotating registers holding the key and the
+/// IV, whose bits are XORed into the feedback every round. The multiplicative depth
+/// per round is unchanged.
+///
+/// # Features
+///
+/// - **Key size**: 128 bits (16 bytes)
+/// - **IV size**: 128 bits (16 bytes)
+/// - **Stream cipher**: encryption and decryption are the same operation
+///
+/// Key, IV and keystream bit ordering are the same as for Trivium.
+pub struct KreyviumCipher;
+
+impl SymmetricCipher for KreyviumCipher {
+    type Key = [u8; 16]; // 128-bit key
+    type Nonce = [u8; 16]; // 128-bit IV
+
+    fn keygen() -> Self::Key {
+        let mut key = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &mut [u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let mut stream = KreyviumStream::new(key, nonce);
+        Ok(plaintext.iter().map(|b| b ^ stream.next_byte()).collect())
+    }
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &mut [u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        Self::encrypt(key, nonce, ciphertext) // Kreyvium is symmetric
+    }
+}
+
+/// Clear Kreyvium keystream generator.
+#[derive(Clone)]
+pub struct KreyviumStream {
+    s: [bool; 288],
+    key: Vec<bool>,
+    iv: Vec<bool>,
+    round: usize,
+}
+
+impl KreyviumStream {
+    /// Loads key and IV and runs the [`WARMUP_ROUNDS`] initialization rounds.
+    pub fn new(key: &[u8; 16], iv: &[u8; 16]) -> Self {
+        let key = bits_from_bytes(key);
+        let iv = bits_from_bytes(iv);
+
+        let mut s = [false; 288];
+        s[..93].copy_from_slice(&key[..93]);
+        s[93..177].copy_from_slice(&iv[..84]);
+        s[177..221].copy_from_slice(&iv[84..]);
+        s[221..287].fill(true);
+
+        let mut stream = KreyviumStream {
+            s,
+            key,
+            iv,
+            round: 0,
+        };
+        for _ in 0..WARMUP_ROUNDS {
+            stream.next_bit();
+        }
+        stream
+    }
+
+    /// Clocks the cipher once and returns the keystream bit.
+    pub fn next_bit(&mut self) -> bool {
+        let k = self.key[self.round % 128];
+        let v = self.iv[self.round % 128];
+        self.round += 1;
+
+        let s = &mut self.s;
+
+        let mut t1 = s[65] ^ s[92];
+        let mut t2 = s[161] ^ s[176];
+        let mut t3 = s[242] ^ s[287] ^ k;
+        let z = t1 ^ t2 ^ t3;
+
+        t1 ^= (s[90] & s[91]) ^ s[170] ^ v;
+        t2 ^= (s[174] & s[175]) ^ s[263];
+        t3 ^= (s[285] & s[286]) ^ s[68];
+
+        s.copy_within(0..92, 1);
+        s[0] = t3;
+        s.copy_within(93..176, 94);
+        s[93] = t1;
+        s.copy_within(177..287, 178);
+        s[177] = t2;
+
+        z
+    }
+
+    /// Returns the next 8 keystream bits packed least significant bit first.
+    pub fn next_byte(&mut self) -> u8 {
+        (0..8).fold(0u8, |acc, i| acc | ((self.next_bit() as u8) << i))
+    }
+
+    /// Returns the next `n` keystream bits.
+    pub fn keystream_bits(&mut self, n: usize) -> Vec<bool> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystream(key: &[u8; 16], iv: &[u8; 16]) -> String {
+        let mut stream = KreyviumStream::new(key, iv);
+        let bytes: Vec<u8> = (0..8).map(|_| stream.next_byte()).collect();
+        hex::encode_upper(bytes)
+    }
+
+    #[test]
+    fn test_reference_zero_key_zero_iv() {
+        assert_eq!(keystream(&[0u8; 16], &[0u8; 16]), "26DCF1F4BC0F1922");
+    }
+
+    #[test]
+    fn test_reference_single_key_bit() {
+        let mut key = [0u8; 16];
+        key[0] = 0x01;
+        assert_eq!(keystream(&key, &[0u8; 16]), "4FD421D4DA3D2C8A");
+    }
+
+    #[test]
+    fn test_reference_single_iv_bit() {
+        let mut iv = [0u8; 16];
+        iv[0] = 0x01;
+        assert_eq!(keystream(&[0u8; 16], &iv), "C9217BA0D762ACA1");
+    }
+
+    #[test]
+    fn test_reference_key_and_iv() {
+        let key: [u8; 16] = hex::decode("0053A6F94C9FF24598EB000000000000")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let iv: [u8; 16] = hex::decode("0D74DB42A91077DE45AC000000000000")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(keystream(&key, &iv), "D1F0303482061111");
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = KreyviumCipher::keygen();
+        let iv: [u8; 16] = rand::random();
+
+        let mut plaintext = b"Hello, Kreyvium!".to_vec();
+        let mut ciphertext = KreyviumCipher::encrypt(&key, &iv, &mut plaintext).unwrap();
+        assert_ne!(ciphertext, plaintext);
+
+        let decrypted = KreyviumCipher::decrypt(&key, &iv, &mut ciphertext).unwrap();
+        assert_eq!(decrypted, b"Hello, Kreyvium!");
+    }
+}
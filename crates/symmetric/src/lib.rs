@@ -6,6 +6,14 @@ pub mod aes;
 pub mod camellia;
 /// ChaCha20 Modules
 pub mod chacha;
+/// ChaCha20-Poly1305 and XChaCha20-Poly1305 Modules
+pub mod chacha_poly1305;
+/// AES-256-GCM Modules
+pub mod gcm;
+/// Kreyvium Modules
+pub mod kreyvium;
+/// Trivium Modules
+pub mod trivium;
 
 /// A trait for symmetric encryption algorithms.
 pub trait SymmetricCipher {
@@ -27,6 +35,35 @@ pub trait SymmetricCipher {
     ) -> Result<Vec<u8>, SymmetricError>;
 }
 
+/// A trait for authenticated encryption with associated data (AEAD).
+///
+/// Unlike [`SymmetricCipher`], the ciphertext produced here carries an
+/// authentication tag covering both the ciphertext and the associated data,
+/// so any modification is detected on decryption and reported as
+/// [`SymmetricError::AuthenticationFailed`].
+pub trait AeadCipher {
+    type Key;
+    type Nonce;
+
+    fn keygen() -> Self::Key;
+
+    /// Encrypts `plaintext` and returns `ciphertext || tag`.
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError>;
+
+    /// Verifies the tag over `ciphertext || tag` and `aad`, then decrypts.
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SymmetricError>;
+}
+
 /// Error type for symmetric encryption operations.
 #[derive(Debug, thiserror::Error)]
 pub enum SymmetricError {
@@ -38,4 +75,7 @@ pub enum SymmetricError {
 
     #[error("Decryption failed: {0}")]
     DecryptError(String),
+
+    #[error("Authentication tag verification failed")]
+    AuthenticationFailed,
 }
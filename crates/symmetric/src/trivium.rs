@@ -0,0 +1,241 @@
+use rand::RngCore;
+
+use crate::{SymmetricCipher, SymmetricError};
+
+/// Number of blank rounds run before the first keystream bit (4 × 288).
+pub const WARMUP_ROUNDS: usize = 1152;
+
+/// Trivium symmetric stream cipher.
+///
+/// Trivium is a bit-oriented synchronous stream cipher from the eSTREAM hardware
+/// portfolio, designed by De Cannière and Preneel. Its 288-bit internal state is
+/// split into three non-linear feedback shift registers of 93, 84 and 111 bits, and
+/// each round produces one keystream bit using only XORs and three ANDs.
+///
+/// That low multiplicative depth is what makes it attractive for transciphering:
+/// evaluating a round over encrypted bits costs three homomorphic ANDs, compared to
+/// the 32-bit additions ChaCha20 needs.
+///
+/// # Features
+///
+/// - **Key size**: 80 bits (10 bytes)
+/// - **IV size**: 80 bits (10 bytes)
+/// - **Stream cipher**: the keystream is XORed with the data, so encryption and
+///   decryption are the same operation
+///
+/// # Bit ordering
+///
+/// Keys and IVs follow the eSTREAM reference implementation: state bit `s1` is the
+/// most significant bit of the last byte, and keystream bits are packed into bytes
+/// least significant bit first. See [`bits_from_bytes`].
+///
+/// # Security
+///
+/// As with any stream cipher, reusing an IV under the same key reveals the XOR of
+/// the plaintexts. Trivium provides no authentication.
+pub struct TriviumCipher;
+
+impl SymmetricCipher for TriviumCipher {
+    type Key = [u8; 10]; // 80-bit key
+    type Nonce = [u8; 10]; // 80-bit IV
+
+    fn keygen() -> Self::Key {
+        let mut key = [0u8; 10];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        plaintext: &mut [u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        let mut stream = TriviumStream::new(key, nonce);
+        Ok(plaintext.iter().map(|b| b ^ stream.next_byte()).collect())
+    }
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &Self::Nonce,
+        ciphertext: &mut [u8],
+    ) -> Result<Vec<u8>, SymmetricError> {
+        Self::encrypt(key, nonce, ciphertext) // Trivium is symmetric
+    }
+}
+
+/// Clear Trivium keystream generator.
+///
+/// The state is stored as `s[i] = s_(i+1)` in the notation of the specification.
+#[derive(Clone)]
+pub struct TriviumStream {
+    s: [bool; 288],
+}
+
+impl TriviumStream {
+    /// Loads key and IV and runs the [`WARMUP_ROUNDS`] initialization rounds.
+    pub fn new(key: &[u8; 10], iv: &[u8; 10]) -> Self {
+        let key_bits = bits_from_bytes(key);
+        let iv_bits = bits_from_bytes(iv);
+
+        let mut s = [false; 288];
+        s[..80].copy_from_slice(&key_bits);
+        s[93..173].copy_from_slice(&iv_bits);
+        s[285] = true;
+        s[286] = true;
+        s[287] = true;
+
+        let mut stream = TriviumStream { s };
+        for _ in 0..WARMUP_ROUNDS {
+            stream.next_bit();
+        }
+        stream
+    }
+
+    /// Clocks the cipher once and returns the keystream bit.
+    pub fn next_bit(&mut self) -> bool {
+        let s = &mut self.s;
+
+        let mut t1 = s[65] ^ s[92];
+        let mut t2 = s[161] ^ s[176];
+        let mut t3 = s[242] ^ s[287];
+        let z = t1 ^ t2 ^ t3;
+
+        t1 ^= (s[90] & s[91]) ^ s[170];
+        t2 ^= (s[174] & s[175]) ^ s[263];
+        t3 ^= (s[285] & s[286]) ^ s[68];
+
+        s.copy_within(0..92, 1);
+        s[0] = t3;
+        s.copy_within(93..176, 94);
+        s[93] = t1;
+        s.copy_within(177..287, 178);
+        s[177] = t2;
+
+        z
+    }
+
+    /// Returns the next 8 keystream bits packed least significant bit first.
+    pub fn next_byte(&mut self) -> u8 {
+        (0..8).fold(0u8, |acc, i| acc | ((self.next_bit() as u8) << i))
+    }
+
+    /// Returns the next `n` keystream bits.
+    pub fn keystream_bits(&mut self, n: usize) -> Vec<bool> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+}
+
+/// Expands key/IV bytes into state bits in eSTREAM reference order.
+///
+/// For an `n`-bit input, element `i` of the result is bit `n - 1 - i` of the input
+/// read least significant bit first, so the first element is the most significant
+/// bit of the last byte.
+pub fn bits_from_bytes(bytes: &[u8]) -> Vec<bool> {
+    let n = bytes.len() * 8;
+    (0..n)
+        .map(|i| {
+            let j = n - 1 - i;
+            (bytes[j / 8] >> (j % 8)) & 1 == 1
+        })
+        .collect()
+}
+
+/// Packs key/IV bits in eSTREAM reference order back into bytes; the inverse of
+/// [`bits_from_bytes`].
+pub fn bytes_from_bits(bits: &[bool]) -> Vec<u8> {
+    let n = bits.len();
+    let mut bytes = vec![0u8; n.div_ceil(8)];
+    for (i, &bit) in bits.iter().enumerate() {
+        let j = n - 1 - i;
+        bytes[j / 8] |= (bit as u8) << (j % 8);
+    }
+    bytes
+}
+
+/// Unpacks data bytes into bits least significant bit first, matching the order
+/// in which keystream bits are consumed.
+pub fn data_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|b| (0..8).map(move |i| (b >> i) & 1 == 1))
+        .collect()
+}
+
+/// Packs bits least significant bit first; the inverse of [`data_bits`].
+pub fn pack_data_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, &b)| acc | ((b as u8) << i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystream(key: &[u8; 10], iv: &[u8; 10], len: usize) -> String {
+        let mut stream = TriviumStream::new(key, iv);
+        let bytes: Vec<u8> = (0..len).map(|_| stream.next_byte()).collect();
+        hex::encode_upper(bytes)
+    }
+
+    #[test]
+    fn test_estream_zero_key_zero_iv() {
+        assert_eq!(
+            keystream(&[0u8; 10], &[0u8; 10], 64),
+            "FBE0BF265859051B517A2E4E239FC97F563203161907CF2DE7A8790FA1B2E9CD\
+             F75292030268B7382B4C1A759AA2599A285549986E74805903801A4CB5A5D4F2"
+        );
+    }
+
+    #[test]
+    fn test_estream_set6_vector0() {
+        let key: [u8; 10] = hex::decode("0053A6F94C9FF24598EB")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let iv: [u8; 10] = hex::decode("0D74DB42A91077DE45AC")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            keystream(&key, &iv, 64),
+            "F4CD954A717F26A7D6930830C4E7CF0819F80E03F25F342C64ADC66ABA7F8A8E\
+             6EAA49F23632AE3CD41A7BD290A0132F81C6D4043B6E397D7388F3A03B5FE358"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = TriviumCipher::keygen();
+        let iv: [u8; 10] = rand::random();
+
+        let mut plaintext = b"Hello, Trivium!".to_vec();
+        let mut ciphertext = TriviumCipher::encrypt(&key, &iv, &mut plaintext).unwrap();
+        assert_ne!(ciphertext, plaintext);
+
+        let decrypted = TriviumCipher::decrypt(&key, &iv, &mut ciphertext).unwrap();
+        assert_eq!(decrypted, b"Hello, Trivium!");
+    }
+
+    #[test]
+    fn test_key_bits_roundtrip() {
+        let key = TriviumCipher::keygen();
+        let bits = bits_from_bytes(&key);
+        assert_eq!(bits[0], key[9] & 0x80 != 0);
+        assert_eq!(bytes_from_bits(&bits), key);
+    }
+
+    #[test]
+    fn test_data_bits_roundtrip() {
+        let data = b"\x01\x80\xa5";
+        let bits = data_bits(data);
+        assert!(bits[0] && !bits[7] && bits[15]);
+        assert_eq!(pack_data_bits(&bits), data);
+    }
+}
@@ -1,7 +1,8 @@
 use criterion::{Criterion, criterion_group, criterion_main};
+use homomorphic::HeContext;
 use rand::RngCore;
 use symmetric::{SymmetricCipher, chacha::ChaCha20Cipher};
-use tfhe::{ConfigBuilder, generate_keys, set_server_key};
+use tfhe::{ConfigBuilder, generate_keys};
 use transciphering::{Transcipher, chacha::ChaChaTfheTranscipher};
 
 /// Benchmark full ChaCha20 → TFHE transciphering pipeline
@@ -9,7 +10,8 @@ fn bench_transciphering(c: &mut Criterion) {
     // --- TFHE KeyGen ---
     let config = ConfigBuilder::default().build();
     let (client_key, server_key) = generate_keys(config);
-    set_server_key(server_key);
+    let ctx = HeContext::new(server_key);
+    let _guard = ctx.enter();
 
     // --- Symmetric key + nonce ---
     let sym_key = ChaCha20Cipher::keygen();
@@ -1,7 +1,10 @@
-use crate::{HomomorphicEncryption, SymmetricCipher, Transcipher, TranscipherError};
+use crate::{
+    HomomorphicEncryption, SymmetricCipher, Transcipher, TranscipherError, TranscipherServer,
+};
 use homomorphic::tfhe_uint::TfheU32;
 use symmetric::chacha::ChaCha20Cipher;
-use tfhe::FheUint32;
+use tfhe::prelude::*;
+use tfhe::{FheUint8, FheUint32};
 
 pub struct ChaChaTfheTranscipher;
 
@@ -32,7 +35,7 @@ impl Transcipher<ChaCha20Cipher, TfheU32> for ChaChaTfheTranscipher {
     fn transcipher_decrypt(
         sym_nonce: &<ChaCha20Cipher as SymmetricCipher>::Nonce,
         he_sk: &<TfheU32 as HomomorphicEncryption>::SecretKey,
-        sym_key_cts: &Vec<<TfheU32 as HomomorphicEncryption>::Ciphertext>,
+        sym_key_cts: &[<TfheU32 as HomomorphicEncryption>::Ciphertext],
         ciphertext: &[u8],
     ) -> Result<Vec<u8>, TranscipherError> {
         // Recover symmetric key by decrypting the 8 TFHE-encrypted u32 words
@@ -52,19 +55,18 @@ impl Transcipher<ChaCha20Cipher, TfheU32> for ChaChaTfheTranscipher {
 }
 
 /// Server-side: homomorphically compute the ChaCha20 block function and return the 16 u32 output words
-/// as ciphertexts. For test completeness this function receives `client_ck` so it can build ciphertexts
-/// for public constants/counter/nonce. In production you would avoid giving the client key to the server.
+/// as ciphertexts. Only the server key needs to be set; the constants, counter and nonce are public
+/// and enter the state as trivial encryptions.
 pub struct ChaChaTfheServer;
 
 impl ChaChaTfheServer {
     /// Compute one ChaCha20 block homomorphically.
     /// - `key_cts` - 8 ciphertexts holding the key words (little-endian u32 words)
-    /// - `client_ck` - client TFHE secret key (used here to encrypt constants for the demo/test)
     /// - `counter` / `nonce` - block counter and 12-byte nonce
+    ///
     /// Returns 16 ciphertexts representing the 16 u32 output words of the ChaCha20 block.
     pub fn generate_homomorphic_chacha_block(
-        key_cts: &Vec<<TfheU32 as HomomorphicEncryption>::Ciphertext>,
-        client_ck: &<TfheU32 as HomomorphicEncryption>::SecretKey,
+        key_cts: &[<TfheU32 as HomomorphicEncryption>::Ciphertext],
         counter: u32,
         nonce: &[u8; 12],
     ) -> Result<Vec<<TfheU32 as HomomorphicEncryption>::Ciphertext>, TranscipherError> {
@@ -83,13 +85,11 @@ impl ChaChaTfheServer {
 
         let mut state: Vec<FheUint32> = Vec::with_capacity(16);
 
-        // Encrypt constants
+        // Constants
         for &c in &constants {
-            let ct = TfheU32::encrypt_public_with_client(client_ck, c)
-                .map_err(|_| TranscipherError::HeError)?;
-            state.push(ct);
+            state.push(TfheU32::encrypt_trivial(c));
         }
-        println!("   - Encrypted constants appended to state");
+        println!("   - Constants appended to state");
 
         // Append key ciphertexts
         for (i, k) in key_cts.iter().enumerate() {
@@ -97,19 +97,15 @@ impl ChaChaTfheServer {
             state.push(k.clone());
         }
 
-        // Encrypt counter
-        let counter_ct = TfheU32::encrypt_public_with_client(client_ck, counter)
-            .map_err(|_| TranscipherError::HeError)?;
-        state.push(counter_ct);
-        println!("   - Counter encrypted and appended");
+        // Counter
+        state.push(TfheU32::encrypt_trivial(counter));
+        println!("   - Counter appended");
 
-        // Encrypt nonce
+        // Nonce
         for i in 0..3 {
             let part = u32::from_le_bytes(nonce[i * 4..(i + 1) * 4].try_into().unwrap());
-            let ct = TfheU32::encrypt_public_with_client(client_ck, part)
-                .map_err(|_| TranscipherError::HeError)?;
-            state.push(ct);
-            println!("   - Nonce word[{}]: 0x{:08x} (encrypted)", i, part);
+            state.push(TfheU32::encrypt_trivial(part));
+            println!("   - Nonce word[{}]: 0x{:08x}", i, part);
         }
 
         println!(" Initial ChaCha state prepared ({} words)", state.len());
@@ -172,11 +168,47 @@ impl ChaChaTfheServer {
     }
 }
 
+impl TranscipherServer<ChaCha20Cipher, TfheU32> for ChaChaTfheServer {
+    type Output = FheUint8;
+
+    /// Turns a ChaCha20 ciphertext into TFHE ciphertexts of the plaintext bytes.
+    /// - `key_cts` - 8 ciphertexts holding the key words, as produced by
+    ///   [`ChaChaTfheTranscipher::transcipher_encrypt`]
+    /// - `nonce` - the public 12-byte nonce the ciphertext was produced under
+    ///
+    /// One homomorphic block is evaluated per 64 bytes of ciphertext, with the block
+    /// counter starting at 0 like [`ChaCha20Cipher`]. Element `i` of the result
+    /// encrypts plaintext byte `i`.
+    fn transcipher(
+        key_cts: &[<TfheU32 as HomomorphicEncryption>::Ciphertext],
+        nonce: &[u8; 12],
+        ciphertext: &[u8],
+    ) -> Result<Vec<FheUint8>, TranscipherError> {
+        let mut out = Vec::with_capacity(ciphertext.len());
+
+        for (block, chunk) in ciphertext.chunks(64).enumerate() {
+            // The 32-bit counter wraps after 256 GiB, past which ChaCha20 refuses to encrypt
+            let counter = u32::try_from(block).map_err(|_| TranscipherError::SymmetricError)?;
+            let keystream = Self::generate_homomorphic_chacha_block(key_cts, counter, nonce)?;
+
+            // Keystream words are serialized little-endian, so byte `i` of the block is
+            // byte `i % 4` of word `i / 4`
+            for (i, &c) in chunk.iter().enumerate() {
+                let word = &keystream[i / 4];
+                let byte: FheUint8 = (word >> (8 * (i % 4)) as u32).cast_into();
+                out.push(byte ^ c);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use homomorphic::HeContext;
     use rand::RngCore;
-    use tfhe::set_server_key;
     use tfhe::{ConfigBuilder, generate_keys};
 
     #[test]
@@ -188,7 +220,8 @@ mod tests {
 
         let config = ConfigBuilder::default().build();
         let (client_key, server_key) = generate_keys(config);
-        set_server_key(server_key.clone());
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
 
         let plaintext = b"hello from client";
         // Client: encrypt plaintext + homomorphically encrypt symmetric key
@@ -199,7 +232,7 @@ mod tests {
         // === SERVER SIDE ===
         // Server: homomorphically computes one ChaCha20 block
         let keystream_cts =
-            ChaChaTfheServer::generate_homomorphic_chacha_block(&key_cts, &client_key, 1, &nonce)
+            ChaChaTfheServer::generate_homomorphic_chacha_block(&key_cts, 1, &nonce)
                 .expect("server ok");
 
         // Server never sees plaintext or key
@@ -213,4 +246,38 @@ mod tests {
 
         assert_eq!(decrypted, plaintext);
     }
+
+    #[test]
+    fn test_chacha_tfhe_transcipher_multi_block() {
+        // === CLIENT SIDE ===
+        let sym_key = ChaCha20Cipher::keygen();
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let config = ConfigBuilder::default().build();
+        let (client_key, server_key) = generate_keys(config);
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        // 70 bytes spans two ChaCha20 blocks
+        let plaintext = b"a ballot that is long enough to need a second ChaCha20 keystream block";
+        assert!(plaintext.len() > 64);
+
+        let (ciphertext, key_cts) =
+            ChaChaTfheTranscipher::transcipher_encrypt(&sym_key, &nonce, &client_key, plaintext)
+                .expect("encrypt ok");
+
+        // === SERVER SIDE ===
+        // Server: ChaCha20 ciphertext -> TFHE ciphertexts of the plaintext bytes
+        let plaintext_cts =
+            ChaChaTfheServer::transcipher(&key_cts, &nonce, &ciphertext).expect("transcipher ok");
+        assert_eq!(plaintext_cts.len(), plaintext.len());
+
+        // === CLIENT SIDE AGAIN ===
+        let decrypted: Vec<u8> = plaintext_cts
+            .iter()
+            .map(|ct| ct.decrypt(&client_key))
+            .collect();
+        assert_eq!(decrypted, plaintext);
+    }
 }
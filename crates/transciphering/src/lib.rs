@@ -2,7 +2,9 @@ use homomorphic::HomomorphicEncryption;
 use symmetric::SymmetricCipher;
 
 pub mod chacha;
-pub use chacha::ChaChaTfheTranscipher;
+pub mod trivium;
+pub use chacha::{ChaChaTfheServer, ChaChaTfheTranscipher};
+pub use trivium::{TriviumTfheServer, TriviumTfheTranscipher};
 
 /// Trait for homomorphic decryption (transciphering) of a symmetric ciphertext
 /// into a homomorphic ciphertext.
@@ -21,7 +23,11 @@ pub use chacha::ChaChaTfheTranscipher;
 ///    a homomorphic ciphertext securely, enabling computation without access to the plaintext.
 ///
 /// # Type Parameters
-/// - `HE`: The homomorphic encryption scheme used (e.g., `TfheU32`).
+/// - `Sym`: The symmetric cipher the data is encrypted under (e.g., `ChaCha20Cipher`, `TriviumCipher`).
+/// - `HE`: The homomorphic encryption scheme used (e.g., `TfheU32`, `TfheBool`).
+///
+/// Both methods run on the client, which holds the HE secret key. The server half lives
+/// in [`TranscipherServer`].
 pub trait Transcipher<Sym, He>
 where
     Sym: SymmetricCipher,
@@ -40,11 +46,34 @@ where
     fn transcipher_decrypt(
         sym_nonce: &Sym::Nonce,
         he_sk: &He::SecretKey,
-        sym_key_ct: &Vec<He::Ciphertext>,
+        sym_key_ct: &[He::Ciphertext],
         ciphertext: &[u8],
     ) -> Result<Vec<u8>, TranscipherError>;
 }
 
+/// Server side of transciphering: turns a symmetric ciphertext into HE ciphertexts of
+/// the plaintext.
+///
+/// Evaluation only needs the HE server key, so nothing here accepts `He::SecretKey`;
+/// public values such as the nonce and cipher constants enter the computation as
+/// trivial encryptions.
+pub trait TranscipherServer<Sym, He>
+where
+    Sym: SymmetricCipher,
+    He: HomomorphicEncryption,
+{
+    /// HE ciphertext holding one unit (byte, bit, ...) of the recovered plaintext.
+    type Output;
+
+    /// Homomorphically evaluates the keystream under the encrypted symmetric key
+    /// and XORs it into `ciphertext`.
+    fn transcipher(
+        sym_key_ct: &[He::Ciphertext],
+        sym_nonce: &Sym::Nonce,
+        ciphertext: &[u8],
+    ) -> Result<Vec<Self::Output>, TranscipherError>;
+}
+
 #[derive(Debug)]
 pub enum TranscipherError {
     SymmetricError,
@@ -0,0 +1,231 @@
+use crate::{
+    HomomorphicEncryption, SymmetricCipher, Transcipher, TranscipherError, TranscipherServer,
+};
+use homomorphic::tfhe_bool::TfheBool;
+use symmetric::trivium::{
+    TriviumCipher, WARMUP_ROUNDS, bits_from_bytes, bytes_from_bits, data_bits,
+};
+use tfhe::FheBool;
+
+pub struct TriviumTfheTranscipher;
+
+impl Transcipher<TriviumCipher, TfheBool> for TriviumTfheTranscipher {
+    fn transcipher_encrypt(
+        sym_key: &<TriviumCipher as SymmetricCipher>::Key,
+        sym_nonce: &<TriviumCipher as SymmetricCipher>::Nonce,
+        he_sk: &<TfheBool as HomomorphicEncryption>::SecretKey,
+        plaintext: &[u8],
+    ) -> Result<
+        (
+            Vec<u8>,
+            Vec<<TfheBool as HomomorphicEncryption>::Ciphertext>,
+        ),
+        TranscipherError,
+    > {
+        // Trivium encrypt (fast)
+        let mut pt = plaintext.to_vec();
+        let ciphertext = TriviumCipher::encrypt(sym_key, sym_nonce, &mut pt)
+            .map_err(|_| TranscipherError::SymmetricError)?;
+
+        // Encrypt the 80 key bits one by one, in the order they are loaded into the state
+        let key_cts = bits_from_bytes(sym_key)
+            .iter()
+            .map(|bit| TfheBool::encrypt(he_sk, bit))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| TranscipherError::HeError)?;
+
+        Ok((ciphertext, key_cts))
+    }
+
+    fn transcipher_decrypt(
+        sym_nonce: &<TriviumCipher as SymmetricCipher>::Nonce,
+        he_sk: &<TfheBool as HomomorphicEncryption>::SecretKey,
+        sym_key_cts: &[<TfheBool as HomomorphicEncryption>::Ciphertext],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, TranscipherError> {
+        // Recover the symmetric key from its encrypted bits
+        let key_bits = sym_key_cts
+            .iter()
+            .map(|ct| TfheBool::decrypt(he_sk, ct))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| TranscipherError::HeError)?;
+        let sym_key: [u8; 10] = bytes_from_bits(&key_bits)
+            .try_into()
+            .map_err(|_| TranscipherError::HeError)?;
+
+        // Trivium decrypt
+        let mut ct = ciphertext.to_vec();
+        TriviumCipher::decrypt(&sym_key, sym_nonce, &mut ct)
+            .map_err(|_| TranscipherError::SymmetricError)
+    }
+}
+
+/// Server-side: evaluates Trivium over encrypted key bits.
+///
+/// Only the server key needs to be set; the IV and the constant state bits are
+/// public and enter the state as trivial encryptions.
+pub struct TriviumTfheServer;
+
+impl TriviumTfheServer {
+    /// Computes the first `n` Trivium keystream bits homomorphically.
+    /// - `key_cts` - 80 ciphertexts holding the key bits, as produced by
+    ///   [`TriviumTfheTranscipher::transcipher_encrypt`]
+    /// - `iv` - the public 10-byte IV
+    pub fn generate_homomorphic_keystream(
+        key_cts: &[FheBool],
+        iv: &[u8; 10],
+        n: usize,
+    ) -> Result<Vec<FheBool>, TranscipherError> {
+        let mut state = TriviumTfheState::new(key_cts, iv)?;
+        Ok((0..n).map(|_| state.next_bit()).collect())
+    }
+}
+
+impl TranscipherServer<TriviumCipher, TfheBool> for TriviumTfheServer {
+    type Output = FheBool;
+
+    /// Turns a Trivium ciphertext into TFHE encryptions of the plaintext bits.
+    ///
+    /// Bit `8 * i + j` of the result encrypts bit `j` (least significant first) of
+    /// plaintext byte `i`.
+    fn transcipher(
+        key_cts: &[FheBool],
+        iv: &[u8; 10],
+        ciphertext: &[u8],
+    ) -> Result<Vec<FheBool>, TranscipherError> {
+        let mut state = TriviumTfheState::new(key_cts, iv)?;
+        Ok(data_bits(ciphertext)
+            .into_iter()
+            .map(|c| &state.next_bit() ^ c)
+            .collect())
+    }
+}
+
+/// Homomorphic Trivium state, laid out like `symmetric::trivium::TriviumStream`.
+struct TriviumTfheState {
+    s: Vec<FheBool>,
+}
+
+impl TriviumTfheState {
+    fn new(key_cts: &[FheBool], iv: &[u8; 10]) -> Result<Self, TranscipherError> {
+        if key_cts.len() != 80 {
+            return Err(TranscipherError::HeError);
+        }
+
+        let iv_bits = bits_from_bytes(iv);
+        let mut s = Vec::with_capacity(288);
+        s.extend(key_cts.iter().cloned());
+        s.extend((80..93).map(|_| TfheBool::encrypt_trivial(false)));
+        s.extend(iv_bits.iter().map(|&b| TfheBool::encrypt_trivial(b)));
+        s.extend((173..285).map(|_| TfheBool::encrypt_trivial(false)));
+        s.extend((285..288).map(|_| TfheBool::encrypt_trivial(true)));
+
+        let mut state = TriviumTfheState { s };
+        for _ in 0..WARMUP_ROUNDS {
+            state.clock();
+        }
+        Ok(state)
+    }
+
+    /// Returns the keystream bit for the current state, then clocks.
+    fn next_bit(&mut self) -> FheBool {
+        let taps = self.taps();
+        let z = &(&taps[0] ^ &taps[1]) ^ &taps[2];
+        self.update(taps);
+        z
+    }
+
+    fn clock(&mut self) {
+        let taps = self.taps();
+        self.update(taps);
+    }
+
+    /// The three linear taps shared by the output bit and the feedback.
+    fn taps(&self) -> [FheBool; 3] {
+        let s = &self.s;
+        [&s[65] ^ &s[92], &s[161] ^ &s[176], &s[242] ^ &s[287]]
+    }
+
+    fn update(&mut self, [a, b, c]: [FheBool; 3]) {
+        let s = &mut self.s;
+
+        let t1 = &(&a ^ &(&s[90] & &s[91])) ^ &s[170];
+        let t2 = &(&b ^ &(&s[174] & &s[175])) ^ &s[263];
+        let t3 = &(&c ^ &(&s[285] & &s[286])) ^ &s[68];
+
+        s[0..93].rotate_right(1);
+        s[0] = t3;
+        s[93..177].rotate_right(1);
+        s[93] = t1;
+        s[177..288].rotate_right(1);
+        s[177] = t2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use homomorphic::HeContext;
+    use symmetric::trivium::{TriviumStream, pack_data_bits};
+
+    #[test]
+    fn test_homomorphic_keystream_matches_clear() {
+        let (client_key, server_key) = TfheBool::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let sym_key = TriviumCipher::keygen();
+        let iv: [u8; 10] = rand::random();
+
+        let key_cts: Vec<FheBool> = bits_from_bytes(&sym_key)
+            .iter()
+            .map(|b| TfheBool::encrypt(&client_key, b).unwrap())
+            .collect();
+
+        // === SERVER SIDE ===
+        let keystream_cts =
+            TriviumTfheServer::generate_homomorphic_keystream(&key_cts, &iv, 16).unwrap();
+
+        // === CLIENT SIDE ===
+        let decrypted: Vec<bool> = keystream_cts
+            .iter()
+            .map(|ct| TfheBool::decrypt(&client_key, ct).unwrap())
+            .collect();
+
+        let expected = TriviumStream::new(&sym_key, &iv).keystream_bits(16);
+        assert_eq!(decrypted, expected);
+    }
+
+    #[test]
+    fn test_trivium_tfhe_transcipher() {
+        let (client_key, server_key) = TfheBool::keygen().unwrap();
+        let ctx = HeContext::new(server_key);
+        let _guard = ctx.enter();
+
+        let sym_key = TriviumCipher::keygen();
+        let iv: [u8; 10] = rand::random();
+        let plaintext = b"hi!";
+
+        // Client: encrypt plaintext + homomorphically encrypt symmetric key
+        let (ciphertext, key_cts) =
+            TriviumTfheTranscipher::transcipher_encrypt(&sym_key, &iv, &client_key, plaintext)
+                .expect("encrypt ok");
+
+        // Server: Trivium ciphertext -> TFHE ciphertexts of the plaintext bits
+        let plaintext_cts = TriviumTfheServer::transcipher(&key_cts, &iv, &ciphertext).unwrap();
+        assert_eq!(plaintext_cts.len(), plaintext.len() * 8);
+
+        // Client decrypts the HE bits
+        let bits: Vec<bool> = plaintext_cts
+            .iter()
+            .map(|ct| TfheBool::decrypt(&client_key, ct).unwrap())
+            .collect();
+        assert_eq!(pack_data_bits(&bits), plaintext);
+
+        // The symmetric path still roundtrips
+        let decrypted =
+            TriviumTfheTranscipher::transcipher_decrypt(&iv, &client_key, &key_cts, &ciphertext)
+                .expect("decrypt ok");
+        assert_eq!(decrypted, plaintext);
+    }
+}
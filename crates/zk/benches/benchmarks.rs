@@ -1,22 +1,23 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use plonky2::field::types::Field;
+use zk::BallotRule;
 use zk::groth;
 use zk::plonk;
 
 // ----------------------------
 // Sample vote generator (u8)
 // ----------------------------
-fn sample_votes_u8(num_voters: usize, num_candidates: usize) -> (Vec<Vec<u8>>, Vec<u8>) {
+fn sample_votes_u8(num_voters: usize, num_candidates: usize) -> (Vec<Vec<u8>>, Vec<u64>) {
     let mut votes = vec![vec![0u8; num_candidates]; num_voters];
 
     for i in 0..num_voters {
         votes[i][i % num_candidates] = 1;
     }
 
-    let mut totals = vec![0u8; num_candidates];
+    let mut totals = vec![0u64; num_candidates];
     for row in &votes {
         for (j, v) in row.iter().enumerate() {
-            totals[j] += v;
+            totals[j] += *v as u64;
         }
     }
 
@@ -32,25 +33,27 @@ fn bench_groth(c: &mut Criterion) {
     // Setup
     c.bench_function("groth_setup_10v_3c", |b| {
         b.iter(|| {
-            black_box(groth::setup(&votes, &totals));
+            black_box(groth::setup(10, 3, BallotRule::ExactlyOne).unwrap());
         })
     });
 
-    let keys = groth::setup(&votes, &totals);
+    let keys = groth::setup(10, 3, BallotRule::ExactlyOne).unwrap();
 
     // Prove
     c.bench_function("groth_prove_10v_3c", |b| {
         b.iter(|| {
-            black_box(groth::prove(&keys, votes.clone(), totals.clone()));
+            black_box(
+                groth::prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne).unwrap(),
+            );
         })
     });
 
-    let proof = groth::prove(&keys, votes.clone(), totals.clone());
+    let proof = groth::prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne).unwrap();
 
     // Verify
     c.bench_function("groth_verify_10v_3c", |b| {
         b.iter(|| {
-            black_box(groth::verify(&keys, &proof, totals.clone()));
+            black_box(groth::verify(&keys, &proof, totals.clone()).unwrap());
         })
     });
 }
@@ -62,9 +65,9 @@ fn bench_plonk(c: &mut Criterion) {
     let num_voters = 10;
     let num_candidates = 3;
 
-    let (votes_u8, totals_u8) = sample_votes_u8(num_voters, num_candidates);
+    let (votes_u8, totals) = sample_votes_u8(num_voters, num_candidates);
 
-    let params = plonk::setup(num_voters, num_candidates);
+    let (prover, verifier) = plonk::setup(num_voters, num_candidates, BallotRule::ExactlyOne);
 
     // Convert to field format
     let votes_field: Vec<Vec<_>> = votes_u8
@@ -76,9 +79,9 @@ fn bench_plonk(c: &mut Criterion) {
         })
         .collect();
 
-    let totals_field: Vec<_> = totals_u8
+    let totals_field: Vec<_> = totals
         .iter()
-        .map(|&v| plonk::F::from_canonical_u64(v as u64))
+        .map(|&v| plonk::F::from_canonical_u64(v))
         .collect();
 
     let witness = plonk::Witness {
@@ -92,23 +95,27 @@ fn bench_plonk(c: &mut Criterion) {
     // Setup benchmark (already done once, but we measure it separately)
     c.bench_function("plonky_setup_10v_3c", |b| {
         b.iter(|| {
-            black_box(plonk::setup(num_voters, num_candidates));
+            black_box(plonk::setup(
+                num_voters,
+                num_candidates,
+                BallotRule::ExactlyOne,
+            ));
         })
     });
 
     // Prove
     c.bench_function("plonky_prove_10v_3c", |b| {
         b.iter(|| {
-            black_box(plonk::prove(&params, &witness));
+            black_box(plonk::prove(&prover, &witness).unwrap());
         })
     });
 
-    let proof = plonk::prove(&params, &witness);
+    let proof = plonk::prove(&prover, &witness).unwrap();
 
     // Verify
     c.bench_function("plonky_verify_10v_3c", |b| {
         b.iter(|| {
-            black_box(plonk::verify(&params, &statement, &proof));
+            black_box(plonk::verify(&verifier, &statement, &proof));
         })
     });
 }
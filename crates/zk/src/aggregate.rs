@@ -0,0 +1,584 @@
+use crate::plonk::{
+    self, C, D, F, Proof, ProverParameters, Statement, VerifierParameters, Witness,
+};
+use crate::{BallotRule, ZkError};
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::BoolTarget;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, ProverCircuitData};
+use plonky2::plonk::config::Hasher;
+use plonky2::plonk::proof::ProofWithPublicInputsTarget;
+
+/// ==========================
+/// Batch Parameters
+/// ==========================
+///
+/// Batch tally circuit for aggregation: the vote matrix of `plonk::setup`, with
+/// a commitment to that matrix appended to its public inputs. The circuit
+/// computes the commitment as a Poseidon hash of a private blinding value and
+/// the witnessed votes (see [`batch_commitment`]), so it cannot be chosen
+/// independently of the ballots being counted. The blinding keeps a small
+/// batch's votes from being recovered by hashing every possible matrix.
+pub struct BatchParameters {
+    pub params: ProverParameters,
+    pub blinding_target: HashOutTarget,
+}
+
+pub fn batch_setup(
+    num_voters: usize,
+    num_candidates: usize,
+    rule: BallotRule,
+) -> (BatchParameters, VerifierParameters) {
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let (vote_targets, sums) =
+        plonk::voting_constraints(&mut builder, num_voters, num_candidates, rule);
+    let blinding_target = builder.add_virtual_hash();
+    let preimage = blinding_target
+        .elements
+        .iter()
+        .chain(vote_targets.iter().flatten())
+        .copied()
+        .collect();
+    let commitment = builder.hash_n_to_hash_no_pad::<PoseidonHash>(preimage);
+    builder.register_public_inputs(&sums);
+    builder.register_public_inputs(&commitment.elements);
+
+    let circuit_data = builder.build::<C>();
+    let verifier = VerifierParameters {
+        verifier_data: circuit_data.verifier_data(),
+    };
+    let params = BatchParameters {
+        params: ProverParameters {
+            prover_data: circuit_data.prover_data(),
+            vote_targets,
+        },
+        blinding_target,
+    };
+
+    (params, verifier)
+}
+
+/// `blinding` should be fresh randomness kept with the batch: together with
+/// the votes it opens the commitment the proof exposes.
+pub fn prove_batch(
+    params: &BatchParameters,
+    witness: &Witness,
+    blinding: HashOut<F>,
+) -> Result<Proof, ZkError> {
+    let mut pw = plonk::partial_witness(&params.params, witness)?;
+    pw.set_hash_target(params.blinding_target, blinding)
+        .map_err(|_| ZkError::ProveError)?;
+
+    let proof = params
+        .params
+        .prover_data
+        .prove(pw)
+        .map_err(|_| ZkError::UnsatisfiedWitness)?;
+
+    Ok(Proof { proof })
+}
+
+/// Commitment a batch proof exposes: `Poseidon(blinding || votes)`, with the
+/// vote matrix flattened row by row.
+pub fn batch_commitment(witness: &Witness, blinding: HashOut<F>) -> HashOut<F> {
+    let preimage: Vec<F> = blinding
+        .elements
+        .iter()
+        .chain(witness.votes.iter().flatten())
+        .copied()
+        .collect();
+    PoseidonHash::hash_no_pad(&preimage)
+}
+
+/// Public inputs of a batch proof: totals followed by the commitment.
+/// `None` if a total is not below the field order.
+pub fn batch_statement(totals: &[u64], commitment: HashOut<F>) -> Option<Statement> {
+    let mut totals = plonk::canonical_totals(totals)?;
+    totals.extend(commitment.elements);
+    Some(Statement { totals })
+}
+
+/// Chain over batch commitments in aggregation order, starting from zero:
+/// `chain = Poseidon(chain || commitment)` per batch. This is the `chain_out`
+/// of an aggregate over exactly those batches.
+pub fn commitment_chain(commitments: &[HashOut<F>]) -> HashOut<F> {
+    commitments.iter().fold(HashOut::ZERO, |chain, commitment| {
+        let inputs = [chain.elements, commitment.elements].concat();
+        PoseidonHash::hash_no_pad(&inputs)
+    })
+}
+
+/// ==========================
+/// Aggregation Parameters
+/// ==========================
+///
+/// Circuit that verifies up to `arity` inner proofs and exposes their summed
+/// totals, the number of batches they cover, and a commitment chain in and
+/// out. Inner proofs are either batch tallies from [`batch_setup`] (public
+/// inputs: totals, commitment) or earlier aggregates (public inputs: totals,
+/// batch count, chain in, chain out).
+///
+/// Each included batch extends the chain by its commitment; each included
+/// aggregate must start where the chain stands and moves it to its own end.
+/// The final chain is thus fixed by the batch commitments in order, and a
+/// batch fed in twice shows up as a repeated commitment. It does not show
+/// which published ballots a batch counted: that needs the batch's votes and
+/// blinding, which stay with whoever proved it.
+///
+/// Unused slots are filled with a copy of a real proof whose include flag is
+/// off, so it is verified but contributes nothing.
+pub struct AggregationParameters {
+    pub prover_data: ProverCircuitData<F, C, D>,
+    pub proof_targets: Vec<ProofWithPublicInputsTarget<D>>,
+    pub include_targets: Vec<BoolTarget>,
+    pub chain_in_target: HashOutTarget,
+}
+
+/// ==========================
+/// Setup
+/// ==========================
+///
+/// `inner_is_aggregate` selects whether `inner` verifies aggregates or batch
+/// tallies.
+pub fn setup(
+    inner: &VerifierParameters,
+    inner_is_aggregate: bool,
+    arity: usize,
+    num_candidates: usize,
+) -> Result<(AggregationParameters, VerifierParameters), ZkError> {
+    let inner_common = &inner.verifier_data.common;
+    let expected_inputs = if inner_is_aggregate {
+        num_candidates + 1 + 2 * NUM_HASH_OUT_ELTS
+    } else {
+        num_candidates + NUM_HASH_OUT_ELTS
+    };
+    if inner_common.num_public_inputs != expected_inputs || arity == 0 {
+        return Err(ZkError::InvalidSetup);
+    }
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let inner_vd = builder.constant_verifier_data::<C>(&inner.verifier_data.verifier_only);
+
+    let mut totals = vec![builder.zero(); num_candidates];
+    let mut batches = builder.zero();
+    let chain_in_target = builder.add_virtual_hash();
+    let mut chain = chain_in_target.elements;
+    let mut proof_targets = vec![];
+    let mut include_targets = vec![];
+
+    for _ in 0..arity {
+        let proof = builder.add_virtual_proof_with_pis(inner_common);
+        builder.verify_proof::<C>(&proof, &inner_vd, inner_common);
+
+        let include = builder.add_virtual_bool_target_safe();
+
+        for (j, total) in totals.iter_mut().enumerate() {
+            let contribution = builder.mul(include.target, proof.public_inputs[j]);
+            *total = builder.add(*total, contribution);
+        }
+
+        // A batch proof counts as one batch; an aggregate carries its own count
+        let count = if inner_is_aggregate {
+            builder.mul(include.target, proof.public_inputs[num_candidates])
+        } else {
+            include.target
+        };
+        batches = builder.add(batches, count);
+
+        let next = if inner_is_aggregate {
+            let pis = &proof.public_inputs[num_candidates + 1..];
+            // An included aggregate must pick the chain up where it stands
+            for (&child_in, &current) in pis[..NUM_HASH_OUT_ELTS].iter().zip(&chain) {
+                let diff = builder.sub(child_in, current);
+                let gated = builder.mul(include.target, diff);
+                builder.assert_zero(gated);
+            }
+            pis[NUM_HASH_OUT_ELTS..].to_vec()
+        } else {
+            let inputs = chain
+                .iter()
+                .chain(&proof.public_inputs[num_candidates..])
+                .copied()
+                .collect();
+            builder
+                .hash_n_to_hash_no_pad::<PoseidonHash>(inputs)
+                .elements
+                .to_vec()
+        };
+        for (current, next) in chain.iter_mut().zip(next) {
+            *current = builder.select(include, next, *current);
+        }
+
+        proof_targets.push(proof);
+        include_targets.push(include);
+    }
+
+    builder.register_public_inputs(&totals);
+    builder.register_public_input(batches);
+    builder.register_public_inputs(&chain_in_target.elements);
+    builder.register_public_inputs(&chain);
+
+    let circuit_data = builder.build::<C>();
+    let verifier = VerifierParameters {
+        verifier_data: circuit_data.verifier_data(),
+    };
+    let prover = AggregationParameters {
+        prover_data: circuit_data.prover_data(),
+        proof_targets,
+        include_targets,
+        chain_in_target,
+    };
+
+    Ok((prover, verifier))
+}
+
+/// ==========================
+/// Aggregate
+/// ==========================
+///
+/// `chain_in` is the commitment chain before these proofs: zero for the first
+/// batches of a tally, otherwise the `chain_out` of the aggregate before them.
+pub fn aggregate(
+    params: &AggregationParameters,
+    proofs: &[Proof],
+    chain_in: HashOut<F>,
+) -> Result<Proof, ZkError> {
+    let arity = params.proof_targets.len();
+    if proofs.is_empty() || proofs.len() > arity {
+        return Err(ZkError::ProveError);
+    }
+
+    let mut pw = PartialWitness::new();
+    pw.set_hash_target(params.chain_in_target, chain_in)
+        .map_err(|_| ZkError::ProveError)?;
+
+    for i in 0..arity {
+        let (proof, include) = match proofs.get(i) {
+            Some(proof) => (proof, true),
+            None => (&proofs[0], false),
+        };
+
+        pw.set_proof_with_pis_target(&params.proof_targets[i], &proof.proof)
+            .map_err(|_| ZkError::ProveError)?;
+        pw.set_bool_target(params.include_targets[i], include)
+            .map_err(|_| ZkError::ProveError)?;
+    }
+
+    let proof = params
+        .prover_data
+        .prove(pw)
+        .map_err(|_| ZkError::ProveError)?;
+
+    Ok(Proof { proof })
+}
+
+/// Public inputs of an aggregate over the batches committed to by
+/// `commitments`, in order: totals, batch count, zero chain in, chain out.
+/// `None` if a total is not below the field order.
+pub fn aggregate_statement(totals: &[u64], commitments: &[HashOut<F>]) -> Option<Statement> {
+    let mut totals = plonk::canonical_totals(totals)?;
+    totals.push(F::from_canonical_usize(commitments.len()));
+    totals.extend(HashOut::<F>::ZERO.elements);
+    totals.extend(commitment_chain(commitments).elements);
+    Some(Statement { totals })
+}
+
+/// Commitment chain an aggregate proof starts from.
+pub fn chain_in(proof: &Proof) -> HashOut<F> {
+    let pis = &proof.proof.public_inputs;
+    HashOut::from_partial(&pis[pis.len() - 2 * NUM_HASH_OUT_ELTS..pis.len() - NUM_HASH_OUT_ELTS])
+}
+
+/// Commitment chain an aggregate proof ends at.
+pub fn chain_out(proof: &Proof) -> HashOut<F> {
+    let pis = &proof.proof.public_inputs;
+    HashOut::from_partial(&pis[pis.len() - NUM_HASH_OUT_ELTS..])
+}
+
+/// ==========================
+/// Incremental Tally
+/// ==========================
+///
+/// Folds batch proofs into aggregates as they arrive, like a binary counter
+/// in base `arity`: level `i` aggregates proofs of level `i - 1`, and a level
+/// is proven as soon as it holds `arity` proofs. Batch proofs come from
+/// [`batch_setup`] and are chained in the order they are pushed.
+pub struct IncrementalTally {
+    arity: usize,
+    num_candidates: usize,
+    batch_verifier: VerifierParameters,
+    levels: Vec<(AggregationParameters, VerifierParameters)>,
+    pending: Vec<Vec<Proof>>,
+    /// Commitment chain over the batches folded so far.
+    chain: HashOut<F>,
+}
+
+impl IncrementalTally {
+    /// Fails with `InvalidSetup` unless `arity >= 2`: a level of one proof
+    /// would fold forever without ever reducing.
+    pub fn new(
+        batch_verifier: VerifierParameters,
+        arity: usize,
+        num_candidates: usize,
+    ) -> Result<Self, ZkError> {
+        if arity < 2 {
+            return Err(ZkError::InvalidSetup);
+        }
+
+        Ok(IncrementalTally {
+            arity,
+            num_candidates,
+            batch_verifier,
+            levels: vec![],
+            pending: vec![vec![]],
+            chain: HashOut::ZERO,
+        })
+    }
+
+    /// Adds one batch proof, aggregating every level that fills up.
+    pub fn push(&mut self, batch_proof: Proof) -> Result<(), ZkError> {
+        self.pending[0].push(batch_proof);
+
+        let mut level = 0;
+        while self.pending[level].len() == self.arity {
+            self.fold(level)?;
+            level += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Aggregates everything still pending into a single proof, returned with
+    /// the parameters that verify it.
+    pub fn finish(mut self) -> Result<(Proof, VerifierParameters), ZkError> {
+        let mut level = 0;
+        loop {
+            let higher_pending = self.pending[level + 1..].iter().any(|p| !p.is_empty());
+
+            if !higher_pending && level > 0 && self.pending[level].len() == 1 {
+                let proof = self.pending[level].pop().ok_or(ZkError::ProveError)?;
+                let (_, verifier) = self.levels.swap_remove(level - 1);
+                return Ok((proof, verifier));
+            }
+
+            if self.pending[level].is_empty() {
+                if !higher_pending {
+                    return Err(ZkError::ProveError);
+                }
+            } else {
+                self.fold(level)?;
+            }
+            level += 1;
+        }
+    }
+
+    /// Aggregates the proofs pending at `level` into one proof at `level + 1`.
+    fn fold(&mut self, level: usize) -> Result<(), ZkError> {
+        while self.levels.len() <= level {
+            let (inner, inner_is_aggregate) = match self.levels.last() {
+                Some((_, verifier)) => (verifier, true),
+                None => (&self.batch_verifier, false),
+            };
+            let params = setup(inner, inner_is_aggregate, self.arity, self.num_candidates)?;
+            self.levels.push(params);
+        }
+        if self.pending.len() <= level + 1 {
+            self.pending.push(vec![]);
+        }
+
+        let proofs = std::mem::take(&mut self.pending[level]);
+        // Batches extend the running chain; aggregates already carry theirs
+        let start = if level == 0 {
+            self.chain
+        } else {
+            chain_in(&proofs[0])
+        };
+        let proof = aggregate(&self.levels[level].0, &proofs, start)?;
+        if level == 0 {
+            self.chain = chain_out(&proof);
+        }
+        self.pending[level + 1].push(proof);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plonky2::field::types::Field64;
+
+    fn witness(votes: Vec<Vec<u64>>) -> Witness {
+        let votes = votes
+            .into_iter()
+            .map(|row| row.into_iter().map(F::from_canonical_u64).collect())
+            .collect();
+        Witness { votes }
+    }
+
+    fn blinding(batch: u8) -> HashOut<F> {
+        HashOut::from_partial(&[F::from_canonical_u8(batch + 1)])
+    }
+
+    /// A batch proof together with the commitment it exposes.
+    fn batch_proof(
+        params: &BatchParameters,
+        votes: Vec<Vec<u64>>,
+        batch: u8,
+    ) -> (Proof, HashOut<F>) {
+        let witness = witness(votes);
+        let proof = prove_batch(params, &witness, blinding(batch)).unwrap();
+        (proof, batch_commitment(&witness, blinding(batch)))
+    }
+
+    #[test]
+    fn test_aggregate_batches() {
+        let (prover, verifier) = batch_setup(2, 2, BallotRule::ExactlyOne);
+        let (agg, agg_verifier) = setup(&verifier, false, 2, 2).unwrap();
+
+        let (a, ca) = batch_proof(&prover, vec![vec![1, 0], vec![1, 0]], 0);
+        assert!(plonk::verify(
+            &verifier,
+            &batch_statement(&[2, 0], ca).unwrap(),
+            &a
+        ));
+        let (b, cb) = batch_proof(&prover, vec![vec![0, 1], vec![1, 0]], 1);
+
+        let proof = aggregate(&agg, &[a, b], HashOut::ZERO).unwrap();
+
+        assert!(plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[3, 1], &[ca, cb]).unwrap(),
+            &proof
+        ));
+        assert!(!plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[4, 0], &[ca, cb]).unwrap(),
+            &proof
+        ));
+        // Same totals and count, batches in another order
+        assert!(!plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[3, 1], &[cb, ca]).unwrap(),
+            &proof
+        ));
+        // Totals congruent mod p are not the same statement
+        assert!(batch_statement(&[2 + F::ORDER, 0], ca).is_none());
+        assert!(aggregate_statement(&[3 + F::ORDER, 1], &[ca, cb]).is_none());
+    }
+
+    #[test]
+    fn test_commitment_is_bound_to_the_votes() {
+        let (prover, verifier) = batch_setup(2, 2, BallotRule::ExactlyOne);
+
+        // Same totals, different ballots
+        let (proof, commitment) = batch_proof(&prover, vec![vec![1, 0], vec![0, 1]], 0);
+        let other = batch_commitment(&witness(vec![vec![0, 1], vec![1, 0]]), blinding(0));
+        assert_ne!(commitment, other);
+
+        assert!(plonk::verify(
+            &verifier,
+            &batch_statement(&[1, 1], commitment).unwrap(),
+            &proof
+        ));
+        assert!(!plonk::verify(
+            &verifier,
+            &batch_statement(&[1, 1], other).unwrap(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_batch_is_visible() {
+        let (prover, verifier) = batch_setup(1, 2, BallotRule::ExactlyOne);
+        let (agg, agg_verifier) = setup(&verifier, false, 2, 2).unwrap();
+
+        // The first batch fed twice in place of the second
+        let (a, ca) = batch_proof(&prover, vec![vec![1, 0]], 0);
+        let (again, _) = batch_proof(&prover, vec![vec![1, 0]], 0);
+        let (_, cb) = batch_proof(&prover, vec![vec![1, 0]], 1);
+        let proof = aggregate(&agg, &[a, again], HashOut::ZERO).unwrap();
+
+        assert!(!plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[2, 0], &[ca, cb]).unwrap(),
+            &proof
+        ));
+        assert!(plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[2, 0], &[ca, ca]).unwrap(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_padding_slots_do_not_count() {
+        let (prover, verifier) = batch_setup(1, 2, BallotRule::ExactlyOne);
+        let (agg, agg_verifier) = setup(&verifier, false, 3, 2).unwrap();
+
+        let (a, ca) = batch_proof(&prover, vec![vec![0, 1]], 0);
+        let proof = aggregate(&agg, &[a], HashOut::ZERO).unwrap();
+
+        assert!(plonk::verify(
+            &agg_verifier,
+            &aggregate_statement(&[0, 1], &[ca]).unwrap(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_incremental_tally() {
+        let (prover, verifier) = batch_setup(1, 2, BallotRule::ExactlyOne);
+        let mut tally = IncrementalTally::new(verifier, 2, 2).unwrap();
+
+        // Five ballots arriving one batch at a time
+        let rows = [[1, 0], [0, 1], [1, 0], [1, 0], [0, 1]];
+        let mut commitments = vec![];
+        for (batch, row) in rows.iter().enumerate() {
+            let (proof, commitment) = batch_proof(&prover, vec![row.to_vec()], batch as u8);
+            tally.push(proof).unwrap();
+            commitments.push(commitment);
+        }
+
+        let (proof, verifier) = tally.finish().unwrap();
+        assert!(plonk::verify(
+            &verifier,
+            &aggregate_statement(&[3, 2], &commitments).unwrap(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_mismatched_inner_circuit_is_rejected() {
+        let (_, verifier) = batch_setup(1, 3, BallotRule::ExactlyOne);
+        assert!(matches!(
+            setup(&verifier, false, 2, 2),
+            Err(ZkError::InvalidSetup)
+        ));
+
+        // Plain tallies carry no commitment
+        let (_, verifier) = plonk::setup(1, 2, BallotRule::ExactlyOne);
+        assert!(matches!(
+            setup(&verifier, false, 2, 2),
+            Err(ZkError::InvalidSetup)
+        ));
+    }
+
+    #[test]
+    fn test_arity_below_two_is_rejected() {
+        for arity in [0, 1] {
+            let (_, verifier) = batch_setup(1, 2, BallotRule::ExactlyOne);
+            assert!(matches!(
+                IncrementalTally::new(verifier, arity, 2),
+                Err(ZkError::InvalidSetup)
+            ));
+        }
+    }
+}
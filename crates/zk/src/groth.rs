@@ -1,14 +1,21 @@
+use crate::{BallotRule, CircuitShape, VoteMatrix, VoteTally, ZeroKnowledge, ZkError};
 use ark_bls12_381::{Bls12_381, Fr};
 use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey, prepare_verifying_key};
 use ark_r1cs_std::{
     alloc::AllocVar,
+    boolean::Boolean,
     eq::EqGadget,
     fields::{FieldVar, fp::FpVar},
 };
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
 use ark_std::rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
 
 /// ==========================
 /// Voting Circuit Definition
@@ -16,20 +23,29 @@ use ark_std::rand::rngs::OsRng;
 #[derive(Clone)]
 pub struct VotingCircuit {
     pub votes: Vec<Vec<u8>>, // private witness
-    pub totals: Vec<u8>,     // public input
+    pub totals: Vec<u64>,    // public input
+    pub rule: BallotRule,    // per-voter row rule
 }
 
 impl ConstraintSynthesizer<Fr> for VotingCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
-        let num_voters = self.votes.len();
         let num_candidates = self.totals.len();
 
-        // Public inputs
+        // Public inputs, range-checked to 64 bits so a total cannot wrap modulo the field
         let total_vars: Vec<FpVar<Fr>> = self
             .totals
             .into_iter()
-            .map(|t| FpVar::new_input(cs.clone(), || Ok(Fr::from(t as u64))))
-            .collect::<Result<_, _>>()?;
+            .map(|t| {
+                let var = FpVar::new_input(cs.clone(), || Ok(Fr::from(t)))?;
+
+                let bits = (0..64)
+                    .map(|i| Boolean::new_witness(cs.clone(), || Ok((t >> i) & 1 == 1)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Boolean::le_bits_to_fp(&bits)?.enforce_equal(&var)?;
+
+                Ok(var)
+            })
+            .collect::<Result<_, SynthesisError>>()?;
 
         // Private votes
         let vote_vars: Vec<Vec<FpVar<Fr>>> = self
@@ -51,12 +67,26 @@ impl ConstraintSynthesizer<Fr> for VotingCircuit {
             })
             .collect::<Result<_, _>>()?;
 
+        // Row constraints: prod over allowed counts c of (row_sum - c) == 0
+        for row in &vote_vars {
+            let row_sum = row
+                .iter()
+                .fold(FpVar::<Fr>::constant(Fr::from(0u64)), |acc, v| acc + v);
+
+            let mut prod = FpVar::<Fr>::constant(Fr::from(1u64));
+            for c in self.rule.allowed_counts() {
+                prod *= &row_sum - FpVar::constant(Fr::from(c));
+            }
+
+            prod.enforce_equal(&FpVar::constant(Fr::from(0u64)))?;
+        }
+
         // Sum constraints
         for j in 0..num_candidates {
             let mut sum = FpVar::<Fr>::constant(Fr::from(0u64));
 
-            for i in 0..num_voters {
-                sum += &vote_vars[i][j];
+            for row in &vote_vars {
+                sum += &row[j];
             }
 
             sum.enforce_equal(&total_vars[j])?;
@@ -66,6 +96,16 @@ impl ConstraintSynthesizer<Fr> for VotingCircuit {
     }
 }
 
+/// Checks the witness against the circuit before proving. Groth16 itself only
+/// does this in debug builds.
+pub(crate) fn is_satisfied<Circuit: ConstraintSynthesizer<Fr>>(
+    circuit: Circuit,
+) -> Result<bool, SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    cs.is_satisfied()
+}
+
 /// ==========================
 /// Serializable Key Container
 /// ==========================
@@ -77,77 +117,202 @@ pub struct ZkKeys {
 /// ==========================
 /// Setup
 /// ==========================
-pub fn setup(votes: &[Vec<u8>], totals: &[u8]) -> ZkKeys {
+///
+/// Keys depend only on the circuit shape: the circuit is synthesized with
+/// all-zero placeholder votes and totals, so no real ballots enter the ceremony.
+pub fn setup(
+    num_voters: usize,
+    num_candidates: usize,
+    rule: BallotRule,
+) -> Result<ZkKeys, ZkError> {
+    if num_voters == 0 || num_candidates == 0 {
+        return Err(ZkError::InvalidSetup);
+    }
+
     let circuit = VotingCircuit {
-        votes: votes.to_vec(),
-        totals: totals.to_vec(),
+        votes: vec![vec![0u8; num_candidates]; num_voters],
+        totals: vec![0u64; num_candidates],
+        rule,
     };
 
     let mut rng = OsRng;
 
-    let (pk, vk) =
-        Groth16::<Bls12_381>::circuit_specific_setup(circuit, &mut rng).expect("setup failed");
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuit, &mut rng)
+        .map_err(|_| ZkError::InvalidSetup)?;
 
     // Serialize using canonical serialization
     let mut pk_bytes = Vec::new();
     pk.serialize_compressed(&mut pk_bytes)
-        .expect("pk serialization failed");
+        .map_err(|e| ZkError::Internal(e.to_string()))?;
 
     let mut vk_bytes = Vec::new();
     vk.serialize_compressed(&mut vk_bytes)
-        .expect("vk serialization failed");
+        .map_err(|e| ZkError::Internal(e.to_string()))?;
 
-    ZkKeys {
+    Ok(ZkKeys {
         pk: pk_bytes,
         vk: vk_bytes,
+    })
+}
+
+/// Hex SHA-256 of the circuit shape, used to name cached keys.
+pub fn shape_hash(num_voters: usize, num_candidates: usize, rule: BallotRule) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"groth16-voting");
+    hasher.update((num_voters as u64).to_le_bytes());
+    hasher.update((num_candidates as u64).to_le_bytes());
+    let (rule_tag, k) = match rule {
+        BallotRule::ExactlyOne => (0u8, 0),
+        BallotRule::AtMostOne => (1, 0),
+        BallotRule::AtMostK(k) => (2, k as u64),
+    };
+    hasher.update([rule_tag]);
+    hasher.update(k.to_le_bytes());
+
+    hex::encode(hasher.finalize())
+}
+
+/// Like `setup`, but reuses keys stored in `cache_dir` for the same shape and
+/// stores freshly generated ones there as `{shape_hash}.pk` / `.vk`.
+pub fn setup_cached(
+    cache_dir: &Path,
+    num_voters: usize,
+    num_candidates: usize,
+    rule: BallotRule,
+) -> Result<ZkKeys, ZkError> {
+    let hash = shape_hash(num_voters, num_candidates, rule);
+    let pk_path = cache_dir.join(format!("{}.pk", hash));
+    let vk_path = cache_dir.join(format!("{}.vk", hash));
+
+    if let (Ok(pk), Ok(vk)) = (fs::read(&pk_path), fs::read(&vk_path)) {
+        return Ok(ZkKeys { pk, vk });
     }
+
+    let keys = setup(num_voters, num_candidates, rule)?;
+
+    fs::create_dir_all(cache_dir).map_err(|e| ZkError::Internal(e.to_string()))?;
+    fs::write(&pk_path, &keys.pk).map_err(|e| ZkError::Internal(e.to_string()))?;
+    fs::write(&vk_path, &keys.vk).map_err(|e| ZkError::Internal(e.to_string()))?;
+
+    Ok(keys)
 }
 
 /// ==========================
 /// Prove
 /// ==========================
-pub fn prove(keys: &ZkKeys, votes: Vec<Vec<u8>>, totals: Vec<u8>) -> Vec<u8> {
+pub fn prove(
+    keys: &ZkKeys,
+    votes: Vec<Vec<u8>>,
+    totals: Vec<u64>,
+    rule: BallotRule,
+) -> Result<Vec<u8>, ZkError> {
     let pk = ProvingKey::<Bls12_381>::deserialize_compressed(&*keys.pk)
-        .expect("pk deserialization failed");
+        .map_err(|_| ZkError::MalformedKey)?;
 
-    let circuit = VotingCircuit { votes, totals };
+    let circuit = VotingCircuit {
+        votes,
+        totals,
+        rule,
+    };
+
+    if !is_satisfied(circuit.clone()).map_err(|_| ZkError::ProveError)? {
+        return Err(ZkError::UnsatisfiedWitness);
+    }
 
     let mut rng = OsRng;
 
     let proof =
-        Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).expect("proof generation failed");
+        Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).map_err(|_| ZkError::ProveError)?;
 
     let mut proof_bytes = Vec::new();
     proof
         .serialize_compressed(&mut proof_bytes)
-        .expect("proof serialization failed");
+        .map_err(|e| ZkError::Internal(e.to_string()))?;
 
-    proof_bytes
+    Ok(proof_bytes)
 }
 
 /// ==========================
 /// Verify
 /// ==========================
-pub fn verify(keys: &ZkKeys, proof_bytes: &[u8], totals: Vec<u8>) -> bool {
+///
+/// `Ok(false)` means a well-formed proof that does not verify for `totals`.
+pub fn verify(keys: &ZkKeys, proof_bytes: &[u8], totals: Vec<u64>) -> Result<bool, ZkError> {
     let vk = VerifyingKey::<Bls12_381>::deserialize_compressed(&*keys.vk)
-        .expect("vk deserialization failed");
+        .map_err(|_| ZkError::MalformedKey)?;
 
     let proof = Proof::<Bls12_381>::deserialize_compressed(proof_bytes)
-        .expect("proof deserialization failed");
+        .map_err(|_| ZkError::MalformedProof)?;
 
     let pvk = prepare_verifying_key(&vk);
 
-    let public_inputs: Vec<Fr> = totals.into_iter().map(|t| Fr::from(t as u64)).collect();
+    let public_inputs: Vec<Fr> = totals.into_iter().map(Fr::from).collect();
+
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs, &proof)
+        .map_err(|_| ZkError::VerifyError)
+}
+
+/// ==========================
+/// ZeroKnowledge Backend
+/// ==========================
+pub struct Groth16Voting;
+
+/// Keys from setup together with the shape they were generated for.
+pub struct Groth16Parameters {
+    pub keys: ZkKeys,
+    pub shape: CircuitShape,
+}
+
+impl ZeroKnowledge for Groth16Voting {
+    type Parameters = Groth16Parameters;
+    type Proof = Vec<u8>;
+    type Statement = VoteTally;
+    type Witness = VoteMatrix;
+
+    fn setup(shape: &CircuitShape) -> Result<Self::Parameters, ZkError> {
+        Ok(Groth16Parameters {
+            keys: setup(shape.num_voters, shape.num_candidates, shape.rule)?,
+            shape: *shape,
+        })
+    }
+
+    fn prove(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+    ) -> Result<Self::Proof, ZkError> {
+        witness.check_dims(params.shape.num_voters, params.shape.num_candidates)?;
+        if statement.totals.len() != params.shape.num_candidates {
+            return Err(ZkError::ProveError);
+        }
+
+        prove(
+            &params.keys,
+            witness.votes.clone(),
+            statement.totals.clone(),
+            params.shape.rule,
+        )
+    }
 
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs, &proof).unwrap_or(false)
+    fn verify(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+    ) -> Result<bool, ZkError> {
+        if statement.totals.len() != params.shape.num_candidates {
+            return Ok(false);
+        }
+
+        verify(&params.keys, proof, statement.totals.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn setup_for(votes: Vec<Vec<u8>>, totals: Vec<u8>) -> ZkKeys {
-        setup(&votes, &totals)
+    fn setup_for(votes: &[Vec<u8>]) -> ZkKeys {
+        setup(votes.len(), votes[0].len(), BallotRule::ExactlyOne).unwrap()
     }
 
     #[test]
@@ -160,11 +325,11 @@ mod tests {
 
         let totals = vec![2, 1];
 
-        let keys = setup_for(votes.clone(), totals.clone());
+        let keys = setup_for(&votes);
 
-        let proof = prove(&keys, votes.clone(), totals.clone());
+        let proof = prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne).unwrap();
 
-        let verified = verify(&keys, &proof, totals.clone());
+        let verified = verify(&keys, &proof, totals.clone()).unwrap();
 
         assert!(verified, "Valid voting proof failed");
     }
@@ -176,26 +341,177 @@ mod tests {
         let correct_totals = vec![2, 1];
         let wrong_totals = vec![3, 0];
 
-        let keys = setup_for(votes.clone(), correct_totals.clone());
+        let keys = setup_for(&votes);
 
-        let proof = prove(&keys, votes.clone(), correct_totals.clone());
+        let proof = prove(
+            &keys,
+            votes.clone(),
+            correct_totals.clone(),
+            BallotRule::ExactlyOne,
+        )
+        .unwrap();
 
-        let verified = verify(&keys, &proof, wrong_totals.clone());
+        let verified = verify(&keys, &proof, wrong_totals.clone()).unwrap();
 
         assert!(!verified, "Verification should fail with wrong totals");
     }
 
     #[test]
-    #[should_panic]
     fn test_non_binary_vote_should_fail_proving() {
         // Invalid vote = 2
         let votes = vec![vec![2, 0], vec![0, 1]];
 
         let totals = vec![2, 1];
 
-        let keys = setup_for(votes.clone(), totals.clone());
+        let keys = setup_for(&votes);
+
+        let result = prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+    }
 
-        // Should panic during proof generation
-        let _proof = prove(&keys, votes.clone(), totals.clone());
+    #[test]
+    fn test_multiple_choices_should_fail_plurality() {
+        // Binary but selects every candidate
+        let votes = vec![vec![1, 1, 1], vec![0, 1, 0]];
+
+        let totals = vec![1, 2, 1];
+
+        let keys = setup_for(&votes);
+
+        let result = prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+    }
+
+    #[test]
+    fn test_approval_within_limit() {
+        let votes = vec![vec![1, 1, 0], vec![0, 1, 0]];
+        let totals = vec![1, 2, 0];
+        let rule = BallotRule::AtMostK(2);
+
+        let keys = setup(votes.len(), votes[0].len(), rule).unwrap();
+        let proof = prove(&keys, votes.clone(), totals.clone(), rule).unwrap();
+
+        assert!(
+            verify(&keys, &proof, totals).unwrap(),
+            "Approval ballot failed"
+        );
+    }
+
+    #[test]
+    fn test_approval_over_limit_should_fail_proving() {
+        let votes = vec![vec![1, 1, 1], vec![0, 1, 0]];
+        let totals = vec![1, 2, 1];
+        let rule = BallotRule::AtMostK(2);
+
+        let keys = setup(votes.len(), votes[0].len(), rule).unwrap();
+        let result = prove(&keys, votes.clone(), totals.clone(), rule);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+    }
+
+    #[test]
+    fn test_abstention_allowed_with_at_most_one() {
+        let votes = vec![vec![0, 0], vec![1, 0]];
+        let totals = vec![1, 0];
+        let rule = BallotRule::AtMostOne;
+
+        let keys = setup(votes.len(), votes[0].len(), rule).unwrap();
+        let proof = prove(&keys, votes.clone(), totals.clone(), rule).unwrap();
+
+        assert!(verify(&keys, &proof, totals).unwrap(), "Abstention failed");
+    }
+
+    #[test]
+    fn test_abstention_should_fail_plurality() {
+        let votes = vec![vec![0, 0], vec![1, 0]];
+        let totals = vec![1, 0];
+
+        let keys = setup_for(&votes);
+        let result = prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+    }
+
+    #[test]
+    fn test_totals_beyond_u8_with_thousands_of_voters() {
+        let num_voters = 2000;
+        let votes: Vec<Vec<u8>> = (0..num_voters)
+            .map(|i| if i % 4 == 0 { vec![0, 1] } else { vec![1, 0] })
+            .collect();
+        let totals = vec![1500, 500];
+
+        let keys = setup_for(&votes);
+        let proof = prove(&keys, votes.clone(), totals.clone(), BallotRule::ExactlyOne).unwrap();
+
+        assert!(verify(&keys, &proof, totals).unwrap(), "Large tally failed");
+
+        // Totals off by a multiple of 2^8 or 2^32 must not verify
+        assert!(!verify(&keys, &proof, vec![1500 + 256, 500]).unwrap());
+        assert!(!verify(&keys, &proof, vec![1500, 500 + (1 << 32)]).unwrap());
+    }
+
+    #[test]
+    fn test_malformed_proof_bytes_are_rejected() {
+        let votes = vec![vec![1, 0], vec![0, 1]];
+        let totals = vec![1, 1];
+
+        let keys = setup_for(&votes);
+
+        let result = verify(&keys, &[0xde, 0xad, 0xbe, 0xef], totals);
+        assert!(matches!(result, Err(ZkError::MalformedProof)));
+    }
+
+    #[test]
+    fn test_malformed_key_bytes_are_rejected() {
+        let votes = vec![vec![1, 0], vec![0, 1]];
+        let totals = vec![1, 1];
+
+        let keys = ZkKeys {
+            pk: vec![1, 2, 3],
+            vk: vec![4, 5, 6],
+        };
+
+        let result = prove(&keys, votes, totals.clone(), BallotRule::ExactlyOne);
+        assert!(matches!(result, Err(ZkError::MalformedKey)));
+
+        let result = verify(&keys, &[], totals);
+        assert!(matches!(result, Err(ZkError::MalformedKey)));
+    }
+
+    #[test]
+    fn test_setup_needs_no_witness() {
+        let keys = setup(3, 2, BallotRule::ExactlyOne).unwrap();
+
+        let votes = vec![vec![0, 1], vec![1, 0], vec![1, 0]];
+        let proof = prove(&keys, votes, vec![2, 1], BallotRule::ExactlyOne).unwrap();
+        assert!(verify(&keys, &proof, vec![2, 1]).unwrap());
+
+        assert!(matches!(
+            setup(0, 2, BallotRule::ExactlyOne),
+            Err(ZkError::InvalidSetup)
+        ));
+    }
+
+    #[test]
+    fn test_cached_keys_are_reused_per_shape() {
+        let dir = std::env::temp_dir().join(format!("groth-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let first = setup_cached(&dir, 2, 2, BallotRule::ExactlyOne).unwrap();
+        let again = setup_cached(&dir, 2, 2, BallotRule::ExactlyOne).unwrap();
+        assert_eq!(first.pk, again.pk);
+        assert_eq!(first.vk, again.vk);
+
+        // A different rule is a different circuit
+        let other = setup_cached(&dir, 2, 2, BallotRule::AtMostOne).unwrap();
+        assert_ne!(first.vk, other.vk);
+        assert_ne!(
+            shape_hash(2, 2, BallotRule::ExactlyOne),
+            shape_hash(2, 2, BallotRule::AtMostOne)
+        );
+
+        let votes = vec![vec![1, 0], vec![0, 1]];
+        let proof = prove(&again, votes, vec![1, 1], BallotRule::ExactlyOne).unwrap();
+        assert!(verify(&first, &proof, vec![1, 1]).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -1,8 +1,82 @@
 //! Lib of zero-knowledge proof systems.
 
+///Recursive aggregation of plonky2 tally proofs
+pub mod aggregate;
 pub mod groth;
+///Merkle tree and inclusion receipts over ballot hashes
+pub mod merkle;
 ///Plonk Module
 pub mod plonk;
+///Universal-setup KZG-PLONK backend. Hand-rolled and not yet independently
+///audited; prefer `plonk` or `groth` until it is
+pub mod universal;
+
+pub use groth::Groth16Voting;
+pub use plonk::PlonkyVoting;
+pub use universal::UniversalVoting;
+
+/// Per-voter rule enforced on each row of the vote matrix, on top of every
+/// entry being binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BallotRule {
+    /// Exactly one candidate selected (plurality).
+    #[default]
+    ExactlyOne,
+    /// At most one candidate selected; an all-zero row is an abstention.
+    AtMostOne,
+    /// At most `k` candidates selected (approval).
+    AtMostK(usize),
+}
+
+impl BallotRule {
+    /// Row sums the rule accepts.
+    pub fn allowed_counts(&self) -> std::ops::RangeInclusive<u64> {
+        match self {
+            BallotRule::ExactlyOne => 1..=1,
+            BallotRule::AtMostOne => 0..=1,
+            BallotRule::AtMostK(k) => 0..=*k as u64,
+        }
+    }
+}
+
+/// Shape of the vote matrix a voting circuit is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitShape {
+    pub num_voters: usize,
+    pub num_candidates: usize,
+    pub rule: BallotRule,
+}
+
+/// Public statement of a voting proof: per-candidate totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteTally {
+    pub totals: Vec<u64>,
+}
+
+/// Private witness of a voting proof: one row of binary entries per voter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteMatrix {
+    pub votes: Vec<Vec<u8>>,
+}
+
+impl VoteMatrix {
+    /// Checks the matrix is `num_voters` rows of `num_candidates` entries.
+    pub(crate) fn check_dims(
+        &self,
+        num_voters: usize,
+        num_candidates: usize,
+    ) -> Result<(), ZkError> {
+        if self.votes.len() != num_voters
+            || self.votes.iter().any(|row| row.len() != num_candidates)
+        {
+            return Err(ZkError::Internal(format!(
+                "vote matrix does not match a {}x{} circuit",
+                num_voters, num_candidates
+            )));
+        }
+        Ok(())
+    }
+}
 
 /// A trait for Zero-Knowledge Proof systems.
 pub trait ZeroKnowledge {
@@ -11,8 +85,8 @@ pub trait ZeroKnowledge {
     type Statement;
     type Witness;
 
-    /// Generate trusted setup parameters.
-    fn setup() -> Result<Self::Parameters, ZkError>;
+    /// Generate trusted setup parameters for a circuit of the given shape.
+    fn setup(shape: &CircuitShape) -> Result<Self::Parameters, ZkError>;
 
     /// Create a proof given parameters, statement and witness.
     fn prove(
@@ -38,9 +112,86 @@ pub enum ZkError {
     #[error("Proof generation failed")]
     ProveError,
 
+    #[error("Witness does not satisfy the circuit")]
+    UnsatisfiedWitness,
+
+    #[error("Malformed key bytes")]
+    MalformedKey,
+
+    #[error("Malformed proof bytes")]
+    MalformedProof,
+
     #[error("Proof verification failed")]
     VerifyError,
 
     #[error("Internal error: {0}")]
     Internal(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Suite every `ZeroKnowledge` voting backend must pass.
+    fn voting_suite<Z>()
+    where
+        Z: ZeroKnowledge<Statement = VoteTally, Witness = VoteMatrix>,
+    {
+        let shape = CircuitShape {
+            num_voters: 3,
+            num_candidates: 2,
+            rule: BallotRule::ExactlyOne,
+        };
+        let params = Z::setup(&shape).unwrap();
+
+        let witness = VoteMatrix {
+            votes: vec![vec![1, 0], vec![0, 1], vec![1, 0]],
+        };
+        let statement = VoteTally { totals: vec![2, 1] };
+
+        // Valid proof verifies
+        let proof = Z::prove(&params, &statement, &witness).unwrap();
+        assert!(Z::verify(&params, &statement, &proof).unwrap());
+
+        // Wrong totals do not verify
+        let wrong = VoteTally { totals: vec![3, 0] };
+        assert!(!Z::verify(&params, &wrong, &proof).unwrap());
+
+        // Statement inconsistent with the witness cannot be proven
+        assert!(matches!(
+            Z::prove(&params, &wrong, &witness),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+
+        // Rows violating the ballot rule cannot be proven
+        let invalid = VoteMatrix {
+            votes: vec![vec![1, 1], vec![0, 1], vec![1, 0]],
+        };
+        let invalid_totals = VoteTally { totals: vec![2, 2] };
+        assert!(matches!(
+            Z::prove(&params, &invalid_totals, &invalid),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+
+        // Wrong matrix dimensions are rejected
+        let short = VoteMatrix {
+            votes: vec![vec![1, 0]],
+        };
+        assert!(Z::prove(&params, &VoteTally { totals: vec![1, 0] }, &short).is_err());
+    }
+
+    #[test]
+    fn test_groth16_voting_suite() {
+        voting_suite::<Groth16Voting>();
+    }
+
+    #[test]
+    fn test_plonky_voting_suite() {
+        voting_suite::<PlonkyVoting>();
+    }
+
+    #[test]
+    fn test_universal_voting_suite() {
+        voting_suite::<UniversalVoting>();
+    }
+}
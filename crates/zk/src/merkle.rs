@@ -0,0 +1,211 @@
+use crate::ZkError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// ==========================
+/// Ballot Merkle Tree
+/// ==========================
+///
+/// Tree over ballot hashes in the layout of RFC 6962: the left subtree of `n`
+/// leaves holds the largest power of two below `n`, and leaves and inner nodes
+/// are hashed under distinct prefixes so one cannot pass for the other. The root
+/// over an election's ballots in tally order names the ballot set it counted.
+fn leaf_hash(leaf: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(leaf);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Size of the left subtree of a tree with `n > 1` leaves.
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Root over `leaves` in order. The empty tree hashes to SHA-256 of nothing.
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaf_hash(&leaves[0]),
+        n => {
+            let k = split(n);
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// Sibling hashes from leaf `index` up to the root, nearest first.
+pub fn inclusion_path(leaves: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    if leaves.len() == 1 {
+        return Some(vec![]);
+    }
+
+    let k = split(leaves.len());
+    let (mut path, sibling) = if index < k {
+        (inclusion_path(&leaves[..k], index)?, root(&leaves[k..]))
+    } else {
+        (inclusion_path(&leaves[k..], index - k)?, root(&leaves[..k]))
+    };
+    path.push(sibling);
+    Some(path)
+}
+
+/// Checks `path` takes `leaf` at `index` of a `tree_size` tree up to `root`.
+pub fn verify_inclusion(
+    leaf: &[u8; 32],
+    index: u64,
+    tree_size: u64,
+    path: &[[u8; 32]],
+    root: &[u8; 32],
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+
+    // RFC 9162, section 2.1.3.2
+    let (mut fnode, mut snode) = (index, tree_size - 1);
+    let mut hash = leaf_hash(leaf);
+    for sibling in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            hash = node_hash(sibling, &hash);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+
+    snode == 0 && hash == *root
+}
+
+/// ==========================
+/// Inclusion Receipt
+/// ==========================
+///
+/// What a voter keeps to check their ballot is in a ballot set. Hashes are hex
+/// so the receipt can be kept as plain JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InclusionReceipt {
+    pub ballot_id: String,
+    /// SHA-256 of the ballot's serialized ciphertexts.
+    pub leaf_hash: String,
+    pub index: u64,
+    pub tree_size: u64,
+    pub root: String,
+    /// Sibling hashes from the leaf up, nearest first.
+    pub path: Vec<String>,
+}
+
+impl InclusionReceipt {
+    /// Receipt for leaf `index` of the tree over `leaves`.
+    pub fn new(ballot_id: &str, leaves: &[[u8; 32]], index: usize) -> Option<Self> {
+        let path = inclusion_path(leaves, index)?;
+        Some(InclusionReceipt {
+            ballot_id: ballot_id.to_string(),
+            leaf_hash: hex::encode(leaves[index]),
+            index: index as u64,
+            tree_size: leaves.len() as u64,
+            root: hex::encode(root(leaves)),
+            path: path.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// Checks the path takes the leaf to the receipt's root.
+    pub fn verify(&self) -> Result<bool, ZkError> {
+        let path = self
+            .path
+            .iter()
+            .map(|h| decode_hash(h))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(verify_inclusion(
+            &decode_hash(&self.leaf_hash)?,
+            self.index,
+            self.tree_size,
+            &path,
+            &decode_hash(&self.root)?,
+        ))
+    }
+}
+
+/// Parses a hex SHA-256 digest.
+pub fn decode_hash(hex_hash: &str) -> Result<[u8; 32], ZkError> {
+    hex::decode(hex_hash)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ZkError::Internal(format!("{} is not a SHA-256 digest", hex_hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| Sha256::digest([i]).into()).collect()
+    }
+
+    #[test]
+    fn test_every_leaf_has_a_valid_path() {
+        for n in 1..=9 {
+            let leaves = hashes(n);
+            let tree_root = root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let path = inclusion_path(&leaves, index).unwrap();
+                assert!(
+                    verify_inclusion(leaf, index as u64, n as u64, &path, &tree_root),
+                    "leaf {} of {}",
+                    index,
+                    n
+                );
+            }
+            assert!(inclusion_path(&leaves, n as usize).is_none());
+        }
+    }
+
+    #[test]
+    fn test_tampered_receipt_fails() {
+        let leaves = hashes(7);
+        let receipt = InclusionReceipt::new("b3", &leaves, 3).unwrap();
+        assert!(receipt.verify().unwrap());
+
+        let mut wrong_leaf = receipt.clone();
+        wrong_leaf.leaf_hash = hex::encode(leaves[4]);
+        assert!(!wrong_leaf.verify().unwrap());
+
+        let mut moved = receipt.clone();
+        moved.index = 4;
+        assert!(!moved.verify().unwrap());
+
+        // The root binds the size; a size with another path shape fails outright
+        let mut resized = receipt.clone();
+        resized.tree_size = 4;
+        assert!(!resized.verify().unwrap());
+
+        // Root of a different ballot set
+        let mut other_root = receipt.clone();
+        other_root.root = hex::encode(root(&leaves[..6]));
+        assert!(!other_root.verify().unwrap());
+
+        let mut short = receipt.clone();
+        short.path.pop();
+        assert!(!short.verify().unwrap());
+    }
+}
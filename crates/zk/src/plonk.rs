@@ -1,22 +1,130 @@
+use crate::{BallotRule, CircuitShape, VoteMatrix, VoteTally, ZeroKnowledge, ZkError};
+use plonky2::field::types::{Field, Field64};
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::circuit_data::{
+    CircuitConfig, CommonCircuitData, ProverCircuitData, VerifierCircuitData,
+    VerifierOnlyCircuitData,
+};
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::util::serialization::{DefaultGateSerializer, DefaultGeneratorSerializer};
+use serde::{Deserialize, Serialize};
 
-const D: usize = 2;
-type C = PoseidonGoldilocksConfig;
+pub(crate) const D: usize = 2;
+pub(crate) type C = PoseidonGoldilocksConfig;
 pub type F = <C as GenericConfig<D>>::F;
 
 /// ==========================
-/// Parameters
+/// Prover Parameters
 /// ==========================
-pub struct Parameters {
-    pub circuit_data: CircuitData<F, C, D>,
+pub struct ProverParameters {
+    pub prover_data: ProverCircuitData<F, C, D>,
     pub vote_targets: Vec<Vec<Target>>,
 }
 
+/// Byte form of `ProverParameters`, for caching a built circuit on disk.
+#[derive(Serialize, Deserialize)]
+struct SerializedProverParameters {
+    prover_data: Vec<u8>,
+    vote_targets: Vec<Vec<Target>>,
+}
+
+impl ProverParameters {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ZkError> {
+        let prover_data = self
+            .prover_data
+            .to_bytes(
+                &DefaultGateSerializer,
+                &DefaultGeneratorSerializer::<C, D>::default(),
+            )
+            .map_err(|e| ZkError::Internal(e.to_string()))?;
+
+        bincode::serialize(&SerializedProverParameters {
+            prover_data,
+            vote_targets: self.vote_targets.clone(),
+        })
+        .map_err(|e| ZkError::Internal(e.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let serialized: SerializedProverParameters =
+            bincode::deserialize(bytes).map_err(|_| ZkError::MalformedKey)?;
+
+        let prover_data = ProverCircuitData::from_bytes(
+            &serialized.prover_data,
+            &DefaultGateSerializer,
+            &DefaultGeneratorSerializer::<C, D>::default(),
+        )
+        .map_err(|_| ZkError::MalformedKey)?;
+
+        Ok(ProverParameters {
+            prover_data,
+            vote_targets: serialized.vote_targets,
+        })
+    }
+}
+
+/// ==========================
+/// Serializable Verifier Data
+/// ==========================
+pub struct VerifierKeys {
+    pub verifier_only: Vec<u8>,
+    pub common: Vec<u8>,
+}
+
+/// ==========================
+/// Verifier Parameters
+/// ==========================
+///
+/// Enough to check tally proofs without building the circuit; can be
+/// restored from `VerifierKeys` alone.
+pub struct VerifierParameters {
+    pub verifier_data: VerifierCircuitData<F, C, D>,
+}
+
+impl VerifierParameters {
+    pub fn to_keys(&self) -> Result<VerifierKeys, ZkError> {
+        let verifier_only = self
+            .verifier_data
+            .verifier_only
+            .to_bytes()
+            .map_err(|e| ZkError::Internal(e.to_string()))?;
+
+        let common = self
+            .verifier_data
+            .common
+            .to_bytes(&DefaultGateSerializer)
+            .map_err(|e| ZkError::Internal(e.to_string()))?;
+
+        Ok(VerifierKeys {
+            verifier_only,
+            common,
+        })
+    }
+
+    pub fn from_keys(keys: &VerifierKeys) -> Result<Self, ZkError> {
+        let verifier_only = VerifierOnlyCircuitData::from_bytes(keys.verifier_only.clone())
+            .map_err(|_| ZkError::MalformedKey)?;
+
+        let common = CommonCircuitData::from_bytes(keys.common.clone(), &DefaultGateSerializer)
+            .map_err(|_| ZkError::MalformedKey)?;
+
+        Ok(VerifierParameters {
+            verifier_data: VerifierCircuitData {
+                verifier_only,
+                common,
+            },
+        })
+    }
+
+    /// Parses proof bytes against this circuit's common data.
+    pub fn proof_from_bytes(&self, bytes: &[u8]) -> Result<Proof, ZkError> {
+        Proof::from_bytes(bytes, &self.verifier_data.common)
+    }
+}
+
 /// ==========================
 /// Statement
 /// ==========================
@@ -38,13 +146,64 @@ pub struct Proof {
     pub proof: ProofWithPublicInputs<F, C, D>,
 }
 
+impl Proof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.proof.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8], common: &CommonCircuitData<F, D>) -> Result<Self, ZkError> {
+        let proof = ProofWithPublicInputs::from_bytes(bytes.to_vec(), common)
+            .map_err(|_| ZkError::MalformedProof)?;
+
+        Ok(Proof { proof })
+    }
+}
+
+/// Totals as field elements, or `None` if one is not below the field order.
+///
+/// `F::from_canonical_u64` only debug-asserts its range and otherwise reduces
+/// mod p, so an unchecked `p + t` would pass for `t`.
+pub fn canonical_totals(totals: &[u64]) -> Option<Vec<F>> {
+    totals
+        .iter()
+        .map(|&t| (t < F::ORDER).then(|| F::from_canonical_u64(t)))
+        .collect()
+}
+
 /// ==========================
 /// Setup
 /// ==========================
-pub fn setup(num_voters: usize, num_candidates: usize) -> Parameters {
+pub fn setup(
+    num_voters: usize,
+    num_candidates: usize,
+    rule: BallotRule,
+) -> (ProverParameters, VerifierParameters) {
     let config = CircuitConfig::standard_recursion_config();
     let mut builder = CircuitBuilder::<F, D>::new(config);
 
+    let (vote_targets, sums) = voting_constraints(&mut builder, num_voters, num_candidates, rule);
+    builder.register_public_inputs(&sums);
+
+    let circuit_data = builder.build::<C>();
+    let verifier = VerifierParameters {
+        verifier_data: circuit_data.verifier_data(),
+    };
+    let prover = ProverParameters {
+        prover_data: circuit_data.prover_data(),
+        vote_targets,
+    };
+
+    (prover, verifier)
+}
+
+/// Adds the vote matrix constraints to `builder`: binary entries and the row
+/// rule. Returns the vote targets and the per-candidate column sums.
+pub(crate) fn voting_constraints(
+    builder: &mut CircuitBuilder<F, D>,
+    num_voters: usize,
+    num_candidates: usize,
+    rule: BallotRule,
+) -> (Vec<Vec<Target>>, Vec<Target>) {
     let mut vote_targets = vec![];
 
     for _ in 0..num_voters {
@@ -62,65 +221,140 @@ pub fn setup(num_voters: usize, num_candidates: usize) -> Parameters {
             row.push(v);
         }
 
+        // Row constraint: prod over allowed counts c of (row_sum - c) == 0
+        let row_sum = builder.add_many(row.iter().copied());
+        let mut prod = builder.one();
+        for c in rule.allowed_counts() {
+            let count = builder.constant(F::from_canonical_u64(c));
+            let diff = builder.sub(row_sum, count);
+            prod = builder.mul(prod, diff);
+        }
+        builder.assert_zero(prod);
+
         vote_targets.push(row);
     }
 
     // Sum constraints
-    for j in 0..num_candidates {
-        let mut sum = builder.zero();
-        for i in 0..num_voters {
-            sum = builder.add(sum, vote_targets[i][j]);
-        }
-        builder.register_public_input(sum);
-    }
-
-    let circuit_data = builder.build::<C>();
-
-    Parameters {
-        circuit_data,
-        vote_targets,
-    }
+    let sums = (0..num_candidates)
+        .map(|j| {
+            let mut sum = builder.zero();
+            for row in &vote_targets {
+                sum = builder.add(sum, row[j]);
+            }
+            sum
+        })
+        .collect();
+
+    (vote_targets, sums)
 }
 
 /// ==========================
 /// Prove
 /// ==========================
-pub fn prove(params: &Parameters, witness: &Witness) -> Proof {
-    let mut pw = PartialWitness::new();
+pub fn prove(params: &ProverParameters, witness: &Witness) -> Result<Proof, ZkError> {
+    let pw = partial_witness(params, witness)?;
 
-    for i in 0..witness.votes.len() {
-        for j in 0..witness.votes[i].len() {
-            let _ = pw.set_target(params.vote_targets[i][j], witness.votes[i][j]);
-        }
+    // Unsatisfied constraints surface as a witness generation error
+    let proof = params
+        .prover_data
+        .prove(pw)
+        .map_err(|_| ZkError::UnsatisfiedWitness)?;
+
+    Ok(Proof { proof })
+}
+
+/// Assigns the vote matrix to the circuit's vote targets.
+pub(crate) fn partial_witness(
+    params: &ProverParameters,
+    witness: &Witness,
+) -> Result<PartialWitness<F>, ZkError> {
+    let num_candidates = params.vote_targets.first().map_or(0, Vec::len);
+    if witness.votes.len() != params.vote_targets.len()
+        || witness.votes.iter().any(|row| row.len() != num_candidates)
+    {
+        return Err(ZkError::ProveError);
     }
 
-    let proof = params.circuit_data.prove(pw).unwrap();
+    let mut pw = PartialWitness::new();
+    for (targets, votes) in params.vote_targets.iter().zip(&witness.votes) {
+        for (&target, &vote) in targets.iter().zip(votes) {
+            pw.set_target(target, vote)
+                .map_err(|_| ZkError::ProveError)?;
+        }
+    }
 
-    Proof { proof }
+    Ok(pw)
 }
 
 /// ==========================
 /// Verify
 /// ==========================
-pub fn verify(params: &Parameters, statement: &Statement, proof: &Proof) -> bool {
-    if params.circuit_data.verify(proof.proof.clone()).is_err() {
+pub fn verify(params: &VerifierParameters, statement: &Statement, proof: &Proof) -> bool {
+    if params.verifier_data.verify(proof.proof.clone()).is_err() {
         return false;
     }
 
     // Ensure public inputs match expected totals
-    for (i, expected) in statement.totals.iter().enumerate() {
-        if proof.proof.public_inputs[i] != *expected {
-            return false;
+    proof.proof.public_inputs == statement.totals
+}
+
+/// ==========================
+/// ZeroKnowledge Backend
+/// ==========================
+pub struct PlonkyVoting;
+
+impl ZeroKnowledge for PlonkyVoting {
+    type Parameters = (ProverParameters, VerifierParameters);
+    type Proof = Proof;
+    type Statement = VoteTally;
+    type Witness = VoteMatrix;
+
+    fn setup(shape: &CircuitShape) -> Result<Self::Parameters, ZkError> {
+        Ok(setup(shape.num_voters, shape.num_candidates, shape.rule))
+    }
+
+    fn prove(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+    ) -> Result<Self::Proof, ZkError> {
+        let (prover, _) = params;
+        witness.check_dims(
+            prover.vote_targets.len(),
+            prover.vote_targets.first().map_or(0, Vec::len),
+        )?;
+
+        let votes = witness
+            .votes
+            .iter()
+            .map(|row| row.iter().map(|&v| F::from_canonical_u8(v)).collect())
+            .collect();
+        let Proof { proof } = prove(prover, &Witness { votes })?;
+
+        let expected = canonical_totals(&statement.totals).ok_or(ZkError::UnsatisfiedWitness)?;
+        if proof.public_inputs != expected {
+            return Err(ZkError::UnsatisfiedWitness);
         }
+
+        Ok(Proof { proof })
     }
 
-    true
+    fn verify(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+    ) -> Result<bool, ZkError> {
+        let Some(totals) = canonical_totals(&statement.totals) else {
+            return Ok(false);
+        };
+
+        Ok(verify(&params.1, &Statement { totals }, proof))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use plonky2::field::types::Field;
 
     fn to_field_matrix(matrix: Vec<Vec<u64>>) -> Vec<Vec<F>> {
         matrix
@@ -138,7 +372,7 @@ mod tests {
         let num_voters = 3;
         let num_candidates = 2;
 
-        let params = setup(num_voters, num_candidates);
+        let (prover, verifier) = setup(num_voters, num_candidates, BallotRule::ExactlyOne);
 
         // Votes:
         // V1: [1,0]
@@ -156,9 +390,9 @@ mod tests {
             totals: totals.clone(),
         };
 
-        let proof = prove(&params, &witness);
+        let proof = prove(&prover, &witness).unwrap();
 
-        let verified = verify(&params, &statement, &proof);
+        let verified = verify(&verifier, &statement, &proof);
         assert!(verified, "Valid voting proof failed");
     }
 
@@ -167,7 +401,7 @@ mod tests {
         let num_voters = 3;
         let num_candidates = 2;
 
-        let params = setup(num_voters, num_candidates);
+        let (prover, verifier) = setup(num_voters, num_candidates, BallotRule::ExactlyOne);
 
         let votes = to_field_matrix(vec![vec![1, 0], vec![0, 1], vec![1, 0]]);
 
@@ -181,26 +415,197 @@ mod tests {
             totals: wrong_totals,
         };
 
-        let proof = prove(&params, &witness);
+        let proof = prove(&prover, &witness).unwrap();
 
-        let verified = verify(&params, &statement, &proof);
+        let verified = verify(&verifier, &statement, &proof);
         assert!(!verified, "Invalid totals should not verify");
     }
 
     #[test]
-    #[should_panic]
     fn test_non_binary_vote_should_fail_proving() {
         let num_voters = 2;
         let num_candidates = 2;
 
-        let params = setup(num_voters, num_candidates);
+        let (prover, _) = setup(num_voters, num_candidates, BallotRule::ExactlyOne);
 
         // Invalid vote value: 2
         let votes = to_field_matrix(vec![vec![2, 0], vec![0, 1]]);
 
         let witness = Witness { votes };
 
-        // This should panic because binary constraint fails
-        let _proof = prove(&params, &witness);
+        // The binary constraint fails
+        assert!(matches!(
+            prove(&prover, &witness),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+    }
+
+    #[test]
+    fn test_multiple_choices_should_fail_plurality() {
+        let (prover, _) = setup(2, 3, BallotRule::ExactlyOne);
+
+        // Binary but selects every candidate
+        let votes = to_field_matrix(vec![vec![1, 1, 1], vec![0, 1, 0]]);
+
+        assert!(matches!(
+            prove(&prover, &Witness { votes }),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+    }
+
+    #[test]
+    fn test_wrong_matrix_shape_is_rejected() {
+        let (prover, _) = setup(2, 3, BallotRule::ExactlyOne);
+
+        let short_row = to_field_matrix(vec![vec![1, 0], vec![0, 1, 0]]);
+        assert!(matches!(
+            prove(&prover, &Witness { votes: short_row }),
+            Err(ZkError::ProveError)
+        ));
+
+        let extra_voter = to_field_matrix(vec![vec![1, 0, 0]; 3]);
+        assert!(matches!(
+            prove(&prover, &Witness { votes: extra_voter }),
+            Err(ZkError::ProveError)
+        ));
+    }
+
+    #[test]
+    fn test_approval_within_limit() {
+        let (prover, verifier) = setup(2, 3, BallotRule::AtMostK(2));
+
+        let votes = to_field_matrix(vec![vec![1, 1, 0], vec![0, 1, 0]]);
+        let statement = Statement {
+            totals: to_field_vec(vec![1, 2, 0]),
+        };
+
+        let proof = prove(&prover, &Witness { votes }).unwrap();
+        assert!(
+            verify(&verifier, &statement, &proof),
+            "Approval ballot failed"
+        );
+    }
+
+    #[test]
+    fn test_approval_over_limit_should_fail_proving() {
+        let (prover, _) = setup(2, 3, BallotRule::AtMostK(2));
+
+        let votes = to_field_matrix(vec![vec![1, 1, 1], vec![0, 1, 0]]);
+
+        assert!(matches!(
+            prove(&prover, &Witness { votes }),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+    }
+
+    #[test]
+    fn test_abstention_allowed_with_at_most_one() {
+        let (prover, verifier) = setup(2, 2, BallotRule::AtMostOne);
+
+        let votes = to_field_matrix(vec![vec![0, 0], vec![1, 0]]);
+        let statement = Statement {
+            totals: to_field_vec(vec![1, 0]),
+        };
+
+        let proof = prove(&prover, &Witness { votes }).unwrap();
+        assert!(verify(&verifier, &statement, &proof), "Abstention failed");
+    }
+
+    #[test]
+    fn test_abstention_should_fail_plurality() {
+        let (prover, _) = setup(2, 2, BallotRule::ExactlyOne);
+
+        let votes = to_field_matrix(vec![vec![0, 0], vec![1, 0]]);
+
+        assert!(matches!(
+            prove(&prover, &Witness { votes }),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
+    }
+
+    #[test]
+    fn test_verifier_restored_from_keys_alone() {
+        let (prover, verifier) = setup(2, 2, BallotRule::ExactlyOne);
+
+        let votes = to_field_matrix(vec![vec![1, 0], vec![1, 0]]);
+        let statement = Statement {
+            totals: to_field_vec(vec![2, 0]),
+        };
+        let proof_bytes = prove(&prover, &Witness { votes }).unwrap().to_bytes();
+
+        // Only bytes cross over to the auditor
+        let keys = verifier.to_keys().unwrap();
+        drop((prover, verifier));
+        let auditor = VerifierParameters::from_keys(&keys).unwrap();
+
+        let proof = auditor.proof_from_bytes(&proof_bytes).unwrap();
+        assert!(verify(&auditor, &statement, &proof));
+
+        let wrong = Statement {
+            totals: to_field_vec(vec![1, 1]),
+        };
+        assert!(!verify(&auditor, &wrong, &proof));
+    }
+
+    #[test]
+    fn test_prover_parameters_round_trip() {
+        let (prover, verifier) = setup(2, 2, BallotRule::ExactlyOne);
+        let restored = ProverParameters::from_bytes(&prover.to_bytes().unwrap()).unwrap();
+
+        let votes = to_field_matrix(vec![vec![0, 1], vec![1, 0]]);
+        let statement = Statement {
+            totals: to_field_vec(vec![1, 1]),
+        };
+
+        let proof = prove(&restored, &Witness { votes }).unwrap();
+        assert!(verify(&verifier, &statement, &proof));
+    }
+
+    #[test]
+    fn test_malformed_bytes_are_rejected() {
+        let (_, verifier) = setup(2, 2, BallotRule::ExactlyOne);
+
+        assert!(matches!(
+            verifier.proof_from_bytes(&[1, 2, 3]),
+            Err(ZkError::MalformedProof)
+        ));
+        assert!(matches!(
+            VerifierParameters::from_keys(&VerifierKeys {
+                verifier_only: vec![0; 4],
+                common: vec![0; 4],
+            }),
+            Err(ZkError::MalformedKey)
+        ));
+        assert!(matches!(
+            ProverParameters::from_bytes(&[0; 4]),
+            Err(ZkError::MalformedKey)
+        ));
+    }
+
+    #[test]
+    fn test_totals_are_not_reduced_mod_p() {
+        use crate::{CircuitShape, VoteMatrix, VoteTally, ZeroKnowledge};
+
+        let params = PlonkyVoting::setup(&CircuitShape {
+            num_voters: 3,
+            num_candidates: 2,
+            rule: BallotRule::ExactlyOne,
+        })
+        .unwrap();
+        let witness = VoteMatrix {
+            votes: vec![vec![1, 0], vec![0, 1], vec![1, 0]],
+        };
+        let proof =
+            PlonkyVoting::prove(&params, &VoteTally { totals: vec![2, 1] }, &witness).unwrap();
+
+        // p + 2 is congruent to 2 but is not the tally that was proven
+        let wrapped = VoteTally {
+            totals: vec![2 + F::ORDER, 1],
+        };
+        assert!(!PlonkyVoting::verify(&params, &wrapped, &proof).unwrap());
+        assert!(matches!(
+            PlonkyVoting::prove(&params, &wrapped, &witness),
+            Err(ZkError::UnsatisfiedWitness)
+        ));
     }
 }
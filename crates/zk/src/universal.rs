@@ -0,0 +1,958 @@
+use crate::{CircuitShape, VoteMatrix, VoteTally, ZeroKnowledge, ZkError};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM, pairing::Pairing};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_poly::{
+    DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
+    univariate::DensePolynomial,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+type Poly = DensePolynomial<Fr>;
+type Domain = Radix2EvaluationDomain<Fr>;
+
+/// Coset shifts separating the `b` and `c` wire columns from `a`.
+const K1: u64 = 7;
+const K2: u64 = 49;
+
+/// Highest degree above the domain size reached by blinded polynomials.
+const DEGREE_OVERHEAD: usize = 6;
+
+/// ==========================
+/// Universal SRS
+/// ==========================
+///
+/// Powers of a secret `tau` from a single ceremony. Any voting circuit with at
+/// most `max_gates` gates can be indexed from it without a new setup.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalSrs {
+    pub powers_g1: Vec<G1Affine>,
+    pub g2: G2Affine,
+    pub tau_g2: G2Affine,
+}
+
+impl UniversalSrs {
+    pub fn setup<R: RngCore>(max_gates: usize, rng: &mut R) -> Self {
+        let n = max_gates.max(4).next_power_of_two();
+        let tau = Fr::rand(rng);
+
+        let mut powers = Vec::with_capacity(n + DEGREE_OVERHEAD);
+        let mut cur = Fr::one();
+        for _ in 0..n + DEGREE_OVERHEAD {
+            powers.push(cur);
+            cur *= tau;
+        }
+
+        let g1 = G1Projective::generator();
+        let powers_g1 =
+            G1Projective::normalize_batch(&powers.iter().map(|p| g1 * p).collect::<Vec<_>>());
+        let g2 = G2Projective::generator();
+
+        UniversalSrs {
+            powers_g1,
+            g2: g2.into_affine(),
+            tau_g2: (g2 * tau).into_affine(),
+        }
+    }
+
+    /// Largest gate count (domain size) this SRS supports.
+    pub fn max_gates(&self) -> usize {
+        self.powers_g1.len().saturating_sub(DEGREE_OVERHEAD)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ZkError> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes)
+            .map_err(|e| ZkError::Internal(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        Self::deserialize_compressed(bytes).map_err(|_| ZkError::MalformedKey)
+    }
+
+    fn commit(&self, poly: &Poly) -> Result<G1Affine, ZkError> {
+        if poly.coeffs.len() > self.powers_g1.len() {
+            return Err(ZkError::InvalidSetup);
+        }
+        Ok(
+            G1Projective::msm_unchecked(&self.powers_g1[..poly.coeffs.len()], &poly.coeffs)
+                .into_affine(),
+        )
+    }
+}
+
+/// ==========================
+/// Voting Circuit (PLONK gates)
+/// ==========================
+///
+/// Gate `i` enforces `qL*a + qR*b + qO*c + qM*a*b + qC + PI = 0` over wires
+/// that index into `values`; wires sharing a variable are copy-constrained.
+/// The first `num_public` gates expose the totals as public inputs. The gate
+/// layout depends only on the shape, never on the votes.
+struct Circuit {
+    values: Vec<Fr>,
+    wires: [Vec<usize>; 3],
+    selectors: [Vec<Fr>; 5], // qL, qR, qO, qM, qC
+    num_public: usize,
+}
+
+/// Variable 0 is an unconstrained filler for unused wires.
+const FILLER: usize = 0;
+
+impl Circuit {
+    fn new() -> Self {
+        Circuit {
+            values: vec![Fr::zero()],
+            wires: Default::default(),
+            selectors: Default::default(),
+            num_public: 0,
+        }
+    }
+
+    fn var(&mut self, value: Fr) -> usize {
+        self.values.push(value);
+        self.values.len() - 1
+    }
+
+    fn gate(&mut self, wires: [usize; 3], q: [Fr; 5]) {
+        for (col, w) in wires.into_iter().enumerate() {
+            self.wires[col].push(w);
+        }
+        for (col, s) in q.into_iter().enumerate() {
+            self.selectors[col].push(s);
+        }
+    }
+
+    fn num_gates(&self) -> usize {
+        self.wires[0].len()
+    }
+
+    /// `out = a + b`
+    fn add(&mut self, a: usize, b: usize) -> usize {
+        let out = self.var(self.values[a] + self.values[b]);
+        self.gate(
+            [a, b, out],
+            [Fr::one(), Fr::one(), -Fr::one(), Fr::zero(), Fr::zero()],
+        );
+        out
+    }
+
+    /// `out = a * b`
+    fn mul(&mut self, a: usize, b: usize) -> usize {
+        let out = self.var(self.values[a] * self.values[b]);
+        self.gate(
+            [a, b, out],
+            [Fr::zero(), Fr::zero(), -Fr::one(), Fr::one(), Fr::zero()],
+        );
+        out
+    }
+
+    /// `out = a - k`
+    fn sub_const(&mut self, a: usize, k: Fr) -> usize {
+        let out = self.var(self.values[a] - k);
+        self.gate(
+            [a, FILLER, out],
+            [Fr::one(), Fr::zero(), -Fr::one(), Fr::zero(), -k],
+        );
+        out
+    }
+
+    fn assert_zero(&mut self, a: usize) {
+        self.gate(
+            [a, FILLER, FILLER],
+            [Fr::one(), Fr::zero(), Fr::zero(), Fr::zero(), Fr::zero()],
+        );
+    }
+
+    fn assert_equal(&mut self, a: usize, b: usize) {
+        self.gate(
+            [a, b, FILLER],
+            [Fr::one(), -Fr::one(), Fr::zero(), Fr::zero(), Fr::zero()],
+        );
+    }
+
+    fn assert_binary(&mut self, a: usize) {
+        self.gate(
+            [a, a, FILLER],
+            [-Fr::one(), Fr::zero(), Fr::zero(), Fr::one(), Fr::zero()],
+        );
+    }
+
+    fn build(shape: &CircuitShape, votes: &[Vec<u8>], totals: &[u64]) -> Self {
+        let mut cs = Circuit::new();
+        let m = shape.num_candidates;
+
+        // Public totals
+        let total_vars: Vec<usize> = (0..m)
+            .map(|j| {
+                let t = cs.var(Fr::from(totals.get(j).copied().unwrap_or(0)));
+                cs.gate(
+                    [t, FILLER, FILLER],
+                    [Fr::one(), Fr::zero(), Fr::zero(), Fr::zero(), Fr::zero()],
+                );
+                t
+            })
+            .collect();
+        cs.num_public = m;
+
+        // Private votes, binary
+        let vote_vars: Vec<Vec<usize>> = (0..shape.num_voters)
+            .map(|i| {
+                (0..m)
+                    .map(|j| {
+                        let v = votes
+                            .get(i)
+                            .and_then(|row| row.get(j))
+                            .copied()
+                            .unwrap_or(0);
+                        let var = cs.var(Fr::from(v));
+                        cs.assert_binary(var);
+                        var
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Row constraints: prod over allowed counts c of (row_sum - c) == 0
+        for row in &vote_vars {
+            let row_sum = row[1..].iter().fold(row[0], |acc, &v| cs.add(acc, v));
+
+            let mut prod = None;
+            for c in shape.rule.allowed_counts() {
+                let diff = cs.sub_const(row_sum, Fr::from(c));
+                prod = Some(match prod {
+                    Some(p) => cs.mul(p, diff),
+                    None => diff,
+                });
+            }
+            if let Some(p) = prod {
+                cs.assert_zero(p);
+            }
+        }
+
+        // Sum constraints
+        for (j, &total) in total_vars.iter().enumerate() {
+            let sum = vote_vars[1..]
+                .iter()
+                .fold(vote_vars[0][j], |acc, row| cs.add(acc, row[j]));
+            cs.assert_equal(sum, total);
+        }
+
+        cs
+    }
+
+    fn is_satisfied(&self, public_inputs: &[Fr]) -> bool {
+        (0..self.num_gates()).all(|i| {
+            let [a, b, c] = [0, 1, 2].map(|col| self.values[self.wires[col][i]]);
+            let [ql, qr, qo, qm, qc] = [0, 1, 2, 3, 4].map(|s| self.selectors[s][i]);
+            let pi = if i < self.num_public {
+                -public_inputs[i]
+            } else {
+                Fr::zero()
+            };
+            ql * a + qr * b + qo * c + qm * a * b + qc + pi == Fr::zero()
+        })
+    }
+}
+
+/// ==========================
+/// Keys
+/// ==========================
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalVerifierKey {
+    pub domain_size: u64,
+    pub num_public: u64,
+    /// Commitments to qL, qR, qO, qM, qC
+    pub selectors: Vec<G1Affine>,
+    /// Commitments to the three permutation polynomials
+    pub sigmas: Vec<G1Affine>,
+    pub g1: G1Affine,
+    pub g2: G2Affine,
+    pub tau_g2: G2Affine,
+}
+
+pub struct UniversalProverKey {
+    pub shape: CircuitShape,
+    srs: UniversalSrs,
+    domain: Domain,
+    selectors: Vec<Poly>,
+    sigmas: Vec<Poly>,
+    sigma_evals: Vec<Vec<Fr>>,
+    vk: UniversalVerifierKey,
+}
+
+/// ==========================
+/// Index
+/// ==========================
+///
+/// Derives the keys for one election shape from the universal SRS. This step
+/// is deterministic and needs no secret, so anyone can recompute and check it.
+pub fn index(
+    srs: &UniversalSrs,
+    shape: &CircuitShape,
+) -> Result<(UniversalProverKey, UniversalVerifierKey), ZkError> {
+    if shape.num_voters == 0 || shape.num_candidates == 0 {
+        return Err(ZkError::InvalidSetup);
+    }
+
+    let cs = Circuit::build(shape, &[], &[]);
+    let n = cs.num_gates().max(4).next_power_of_two();
+    if n > srs.max_gates() {
+        return Err(ZkError::InvalidSetup);
+    }
+    let domain = Domain::new(n).ok_or(ZkError::InvalidSetup)?;
+
+    let selectors: Vec<Poly> = cs
+        .selectors
+        .iter()
+        .map(|col| interpolate(domain, col))
+        .collect();
+
+    let sigma_evals = permutation(&cs, domain);
+    let sigmas: Vec<Poly> = sigma_evals
+        .iter()
+        .map(|col| interpolate(domain, col))
+        .collect();
+
+    let vk = UniversalVerifierKey {
+        domain_size: n as u64,
+        num_public: cs.num_public as u64,
+        selectors: selectors
+            .iter()
+            .map(|p| srs.commit(p))
+            .collect::<Result<_, _>>()?,
+        sigmas: sigmas
+            .iter()
+            .map(|p| srs.commit(p))
+            .collect::<Result<_, _>>()?,
+        g1: srs.powers_g1[0],
+        g2: srs.g2,
+        tau_g2: srs.tau_g2,
+    };
+
+    // Only the powers this circuit needs are kept with the prover key
+    let mut trimmed = srs.clone();
+    trimmed.powers_g1.truncate(n + DEGREE_OVERHEAD);
+
+    let pk = UniversalProverKey {
+        shape: *shape,
+        srs: trimmed,
+        domain,
+        selectors,
+        sigmas,
+        sigma_evals,
+        vk: vk.clone(),
+    };
+
+    Ok((pk, vk))
+}
+
+/// Values of the permutation polynomials on the domain: each wire position
+/// points to the next position holding the same variable.
+fn permutation(cs: &Circuit, domain: Domain) -> Vec<Vec<Fr>> {
+    let n = domain.size();
+    let shifts = [Fr::one(), Fr::from(K1), Fr::from(K2)];
+    let label = |col: usize, row: usize| shifts[col] * domain.element(row);
+
+    // Positions of every variable, padding rows included
+    let mut positions: Vec<Vec<(usize, usize)>> = vec![vec![]; cs.values.len()];
+    for col in 0..3 {
+        for row in 0..n {
+            let var = cs.wires[col].get(row).copied().unwrap_or(FILLER);
+            positions[var].push((col, row));
+        }
+    }
+
+    let mut sigma = vec![vec![Fr::zero(); n]; 3];
+    for cycle in positions.iter().filter(|p| !p.is_empty()) {
+        for (k, &(col, row)) in cycle.iter().enumerate() {
+            let (next_col, next_row) = cycle[(k + 1) % cycle.len()];
+            sigma[col][row] = label(next_col, next_row);
+        }
+    }
+    sigma
+}
+
+/// ==========================
+/// Proof
+/// ==========================
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+struct UniversalProof {
+    wires: Vec<G1Affine>, // a, b, c
+    z: G1Affine,
+    t: Vec<G1Affine>, // t_lo, t_mid, t_hi
+    w_zeta: G1Affine,
+    w_zeta_omega: G1Affine,
+    evals: Vec<Fr>, // a, b, c, sigma1, sigma2 at zeta
+    z_omega: Fr,    // z at zeta * omega
+}
+
+/// Fiat-Shamir transcript over SHA-256.
+struct Transcript(Vec<u8>);
+
+impl Transcript {
+    fn new(vk: &UniversalVerifierKey, public_inputs: &[Fr]) -> Result<Self, ZkError> {
+        let mut t = Transcript(b"zk-universal-voting".to_vec());
+        t.append(vk)?;
+        t.append(&public_inputs.to_vec())?;
+        Ok(t)
+    }
+
+    fn append<T: CanonicalSerialize>(&mut self, item: &T) -> Result<(), ZkError> {
+        item.serialize_compressed(&mut self.0)
+            .map_err(|e| ZkError::Internal(e.to_string()))
+    }
+
+    fn challenge(&mut self, label: &[u8]) -> Fr {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.0);
+        hasher.update(label);
+        let c = Fr::from_le_bytes_mod_order(&hasher.finalize());
+        // Infallible for field elements written into a Vec
+        let _ = c.serialize_compressed(&mut self.0);
+        c
+    }
+}
+
+/// ==========================
+/// Prove
+/// ==========================
+pub fn prove(
+    pk: &UniversalProverKey,
+    votes: &[Vec<u8>],
+    totals: &[u64],
+) -> Result<Vec<u8>, ZkError> {
+    let shape = &pk.shape;
+    if votes.len() != shape.num_voters
+        || votes.iter().any(|row| row.len() != shape.num_candidates)
+        || totals.len() != shape.num_candidates
+    {
+        return Err(ZkError::ProveError);
+    }
+
+    let cs = Circuit::build(shape, votes, totals);
+    let public_inputs: Vec<Fr> = totals.iter().map(|&t| Fr::from(t)).collect();
+    if !cs.is_satisfied(&public_inputs) {
+        return Err(ZkError::UnsatisfiedWitness);
+    }
+
+    let domain = pk.domain;
+    let n = domain.size();
+    let omega = domain.group_gen();
+    let shifts = [Fr::one(), Fr::from(K1), Fr::from(K2)];
+    let mut rng = OsRng;
+    let mut transcript = Transcript::new(&pk.vk, &public_inputs)?;
+
+    // Round 1: blinded wire polynomials
+    let wire_evals: Vec<Vec<Fr>> = (0..3)
+        .map(|col| {
+            (0..n)
+                .map(|row| cs.values[cs.wires[col].get(row).copied().unwrap_or(FILLER)])
+                .collect()
+        })
+        .collect();
+    let wires: Vec<Poly> = wire_evals
+        .iter()
+        .map(|evals| blind(domain, evals, 2, &mut rng))
+        .collect();
+    let wire_comms = wires
+        .iter()
+        .map(|p| pk.srs.commit(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    transcript.append(&wire_comms)?;
+
+    // Round 2: permutation accumulator
+    let beta = transcript.challenge(b"beta");
+    let gamma = transcript.challenge(b"gamma");
+
+    let mut z_evals = Vec::with_capacity(n);
+    let mut acc = Fr::one();
+    for (row, point) in domain.elements().enumerate() {
+        z_evals.push(acc);
+        let mut num = Fr::one();
+        let mut den = Fr::one();
+        for ((wires, sigmas), shift) in wire_evals.iter().zip(&pk.sigma_evals).zip(shifts) {
+            num *= wires[row] + beta * shift * point + gamma;
+            den *= wires[row] + beta * sigmas[row] + gamma;
+        }
+        acc *= num * den.inverse().ok_or(ZkError::ProveError)?;
+    }
+    let z = blind(domain, &z_evals, 3, &mut rng);
+    let z_comm = pk.srs.commit(&z)?;
+    transcript.append(&z_comm)?;
+
+    // Round 3: quotient
+    let alpha = transcript.challenge(b"alpha");
+
+    let pi_poly = interpolate(
+        domain,
+        &(0..n)
+            .map(|i| public_inputs.get(i).map_or(Fr::zero(), |x| -*x))
+            .collect::<Vec<_>>(),
+    );
+    let l1 = interpolate(
+        domain,
+        &(0..n)
+            .map(|i| if i == 0 { Fr::one() } else { Fr::zero() })
+            .collect::<Vec<_>>(),
+    );
+    let x = Poly::from_coefficients_vec(vec![Fr::zero(), Fr::one()]);
+    let constant = |c: Fr| Poly::from_coefficients_vec(vec![c]);
+    let z_shifted = shift(&z, omega);
+    let [a, b, c] = [&wires[0], &wires[1], &wires[2]];
+    let [ql, qr, qo, qm, qc] = [0, 1, 2, 3, 4].map(|i| &pk.selectors[i]);
+
+    let gate = &(&(&(&(&(a * b) * qm) + &(a * ql)) + &(b * qr)) + &(c * qo)) + &(&pi_poly + qc);
+
+    let mut id_terms = z.clone();
+    let mut sigma_terms = z_shifted.clone();
+    for col in 0..3 {
+        let id = &(&x * &constant(beta * shifts[col])) + &constant(gamma);
+        id_terms = &id_terms * &(&wires[col] + &id);
+        let sigma = &(&pk.sigmas[col] * &constant(beta)) + &constant(gamma);
+        sigma_terms = &sigma_terms * &(&wires[col] + &sigma);
+    }
+    let perm = &(&id_terms - &sigma_terms) * &constant(alpha);
+    let boundary = &(&(&z - &constant(Fr::one())) * &l1) * &constant(alpha.square());
+
+    let numerator = &(&gate + &perm) + &boundary;
+    let (t, remainder) = numerator.divide_by_vanishing_poly(domain);
+    if !remainder.is_zero() {
+        return Err(ZkError::UnsatisfiedWitness);
+    }
+
+    let mut t_coeffs = t.coeffs.clone();
+    t_coeffs.resize(t_coeffs.len().max(3 * n), Fr::zero());
+    let t_lo = Poly::from_coefficients_slice(&t_coeffs[..n]);
+    let t_mid = Poly::from_coefficients_slice(&t_coeffs[n..2 * n]);
+    let t_hi = Poly::from_coefficients_slice(&t_coeffs[2 * n..]);
+    let t_comms = [&t_lo, &t_mid, &t_hi]
+        .iter()
+        .map(|p| pk.srs.commit(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    transcript.append(&t_comms)?;
+
+    // Round 4: evaluations
+    let zeta = transcript.challenge(b"zeta");
+    let evals = vec![
+        a.evaluate(&zeta),
+        b.evaluate(&zeta),
+        c.evaluate(&zeta),
+        pk.sigmas[0].evaluate(&zeta),
+        pk.sigmas[1].evaluate(&zeta),
+    ];
+    let z_omega = z.evaluate(&(zeta * omega));
+    transcript.append(&evals)?;
+    transcript.append(&z_omega)?;
+
+    // Round 5: linearisation and openings
+    let v = transcript.challenge(b"v");
+    let [ea, eb, ec, es1, es2] = [evals[0], evals[1], evals[2], evals[3], evals[4]];
+    let zh_zeta = domain.evaluate_vanishing_polynomial(zeta);
+    let zeta_n = zeta.pow([n as u64]);
+    let l1_zeta = l1.evaluate(&zeta);
+
+    let perm_id = alpha
+        * (ea + beta * zeta + gamma)
+        * (eb + beta * shifts[1] * zeta + gamma)
+        * (ec + beta * shifts[2] * zeta + gamma);
+    let perm_sigma = alpha * (ea + beta * es1 + gamma) * (eb + beta * es2 + gamma) * z_omega;
+
+    let r = &(&(&(&(&(&(qm * &constant(ea * eb)) + &(ql * &constant(ea)))
+        + &(qr * &constant(eb)))
+        + &(qo * &constant(ec)))
+        + qc)
+        + &(&z * &constant(perm_id + alpha.square() * l1_zeta)))
+        - &(&(&pk.sigmas[2] * &constant(perm_sigma * beta))
+            + &(&(&(&t_lo + &(&t_mid * &constant(zeta_n)))
+                + &(&t_hi * &constant(zeta_n.square())))
+                * &constant(zh_zeta)));
+    let r0 = pi_poly.evaluate(&zeta) - perm_sigma * (ec + gamma) - alpha.square() * l1_zeta;
+    let r = &r + &constant(r0);
+
+    let mut opened = r;
+    let mut power = v;
+    for (poly, eval) in [a, b, c, &pk.sigmas[0], &pk.sigmas[1]]
+        .into_iter()
+        .zip(evals.iter())
+    {
+        opened = &opened + &(&(poly - &constant(*eval)) * &constant(power));
+        power *= v;
+    }
+    let w_zeta = pk.srs.commit(&divide_by_linear(&opened, zeta))?;
+    let w_zeta_omega = pk
+        .srs
+        .commit(&divide_by_linear(&(&z - &constant(z_omega)), zeta * omega))?;
+    // The verifier's batching challenge `u` must come after the openings
+    transcript.append(&w_zeta)?;
+    transcript.append(&w_zeta_omega)?;
+
+    let proof = UniversalProof {
+        wires: wire_comms,
+        z: z_comm,
+        t: t_comms,
+        w_zeta,
+        w_zeta_omega,
+        evals,
+        z_omega,
+    };
+
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_compressed(&mut proof_bytes)
+        .map_err(|e| ZkError::Internal(e.to_string()))?;
+
+    Ok(proof_bytes)
+}
+
+/// ==========================
+/// Verify
+/// ==========================
+pub fn verify(
+    vk: &UniversalVerifierKey,
+    proof_bytes: &[u8],
+    totals: &[u64],
+) -> Result<bool, ZkError> {
+    let proof =
+        UniversalProof::deserialize_compressed(proof_bytes).map_err(|_| ZkError::MalformedProof)?;
+    if proof.wires.len() != 3 || proof.t.len() != 3 || proof.evals.len() != 5 {
+        return Err(ZkError::MalformedProof);
+    }
+    if vk.selectors.len() != 5 || vk.sigmas.len() != 3 {
+        return Err(ZkError::MalformedKey);
+    }
+    if totals.len() as u64 != vk.num_public {
+        return Ok(false);
+    }
+
+    let n = vk.domain_size as usize;
+    let domain = Domain::new(n).ok_or(ZkError::MalformedKey)?;
+    let omega = domain.group_gen();
+    let shifts = [Fr::one(), Fr::from(K1), Fr::from(K2)];
+    let public_inputs: Vec<Fr> = totals.iter().map(|&t| Fr::from(t)).collect();
+
+    // Replay the transcript
+    let mut transcript = Transcript::new(vk, &public_inputs)?;
+    transcript.append(&proof.wires)?;
+    let beta = transcript.challenge(b"beta");
+    let gamma = transcript.challenge(b"gamma");
+    transcript.append(&proof.z)?;
+    let alpha = transcript.challenge(b"alpha");
+    transcript.append(&proof.t)?;
+    let zeta = transcript.challenge(b"zeta");
+    transcript.append(&proof.evals)?;
+    transcript.append(&proof.z_omega)?;
+    let v = transcript.challenge(b"v");
+    // Squeezed after the openings, or a prover knowing `u` could solve for them
+    transcript.append(&proof.w_zeta)?;
+    transcript.append(&proof.w_zeta_omega)?;
+    let u = transcript.challenge(b"u");
+
+    let [ea, eb, ec, es1, es2] = [
+        proof.evals[0],
+        proof.evals[1],
+        proof.evals[2],
+        proof.evals[3],
+        proof.evals[4],
+    ];
+    let zh_zeta = domain.evaluate_vanishing_polynomial(zeta);
+    let zeta_n = zeta.pow([n as u64]);
+    let lagrange = domain.evaluate_all_lagrange_coefficients(zeta);
+    let l1_zeta = lagrange[0];
+    let pi_zeta: Fr = public_inputs
+        .iter()
+        .zip(&lagrange)
+        .map(|(x, l)| -*x * l)
+        .sum();
+
+    let perm_id = alpha
+        * (ea + beta * zeta + gamma)
+        * (eb + beta * shifts[1] * zeta + gamma)
+        * (ec + beta * shifts[2] * zeta + gamma);
+    let perm_sigma = alpha * (ea + beta * es1 + gamma) * (eb + beta * es2 + gamma) * proof.z_omega;
+    let r0 = pi_zeta - perm_sigma * (ec + gamma) - alpha.square() * l1_zeta;
+
+    // [D] = linearised polynomial without its constant term r0
+    let mut bases = vec![
+        vk.selectors[3],
+        vk.selectors[0],
+        vk.selectors[1],
+        vk.selectors[2],
+        vk.selectors[4],
+        proof.z,
+        vk.sigmas[2],
+        proof.t[0],
+        proof.t[1],
+        proof.t[2],
+    ];
+    let mut scalars = vec![
+        ea * eb,
+        ea,
+        eb,
+        ec,
+        Fr::one(),
+        perm_id + alpha.square() * l1_zeta + u,
+        -perm_sigma * beta,
+        -zh_zeta,
+        -zh_zeta * zeta_n,
+        -zh_zeta * zeta_n.square(),
+    ];
+
+    // F = [D] + sum v^i [opened polys] + u [z]
+    let mut power = v;
+    for comm in [
+        proof.wires[0],
+        proof.wires[1],
+        proof.wires[2],
+        vk.sigmas[0],
+        vk.sigmas[1],
+    ] {
+        bases.push(comm);
+        scalars.push(power);
+        power *= v;
+    }
+
+    // E = (-r0 + sum v^i evals + u z_omega) [1]
+    let mut e = -r0 + u * proof.z_omega;
+    let mut power = v;
+    for eval in &proof.evals {
+        e += power * eval;
+        power *= v;
+    }
+    bases.push(vk.g1);
+    scalars.push(-e);
+
+    // Fold the opening points into the left-hand side
+    bases.push(proof.w_zeta);
+    scalars.push(zeta);
+    bases.push(proof.w_zeta_omega);
+    scalars.push(u * zeta * omega);
+
+    let rhs = G1Projective::msm_unchecked(&bases, &scalars);
+    let lhs = proof.w_zeta.into_group() + proof.w_zeta_omega.into_group() * u;
+
+    let check = Bls12_381::multi_pairing([lhs, -rhs], [vk.tau_g2, vk.g2]);
+    Ok(check.is_zero())
+}
+
+/// Interpolates evaluations over the domain.
+fn interpolate(domain: Domain, evals: &[Fr]) -> Poly {
+    let mut evals = evals.to_vec();
+    evals.resize(domain.size(), Fr::zero());
+    Poly::from_coefficients_vec(domain.ifft(&evals))
+}
+
+/// Interpolates and adds a random multiple of the vanishing polynomial.
+fn blind<R: RngCore>(domain: Domain, evals: &[Fr], num_blinds: usize, rng: &mut R) -> Poly {
+    let blinding = Poly::from_coefficients_vec((0..num_blinds).map(|_| Fr::rand(rng)).collect());
+    &interpolate(domain, evals) + &blinding.mul_by_vanishing_poly(domain)
+}
+
+/// `p(X * omega)`
+fn shift(p: &Poly, omega: Fr) -> Poly {
+    let mut power = Fr::one();
+    let coeffs = p
+        .coeffs
+        .iter()
+        .map(|c| {
+            let shifted = *c * power;
+            power *= omega;
+            shifted
+        })
+        .collect();
+    Poly::from_coefficients_vec(coeffs)
+}
+
+/// `(p(X) - p(point)) / (X - point)` by synthetic division.
+fn divide_by_linear(p: &Poly, point: Fr) -> Poly {
+    if p.coeffs.len() < 2 {
+        return Poly::zero();
+    }
+    let mut quotient = vec![Fr::zero(); p.coeffs.len() - 1];
+    let mut carry = Fr::zero();
+    for i in (1..p.coeffs.len()).rev() {
+        carry = p.coeffs[i] + carry * point;
+        quotient[i - 1] = carry;
+    }
+    Poly::from_coefficients_vec(quotient)
+}
+
+/// ==========================
+/// ZeroKnowledge Backend
+/// ==========================
+///
+/// `setup` runs a fresh ceremony sized to the shape; services holding a shared
+/// `UniversalSrs` should call `index` directly instead.
+pub struct UniversalVoting;
+
+impl ZeroKnowledge for UniversalVoting {
+    type Parameters = (UniversalProverKey, UniversalVerifierKey);
+    type Proof = Vec<u8>;
+    type Statement = VoteTally;
+    type Witness = VoteMatrix;
+
+    fn setup(shape: &CircuitShape) -> Result<Self::Parameters, ZkError> {
+        let cs = Circuit::build(shape, &[], &[]);
+        let srs = UniversalSrs::setup(cs.num_gates(), &mut OsRng);
+        index(&srs, shape)
+    }
+
+    fn prove(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+    ) -> Result<Self::Proof, ZkError> {
+        let (pk, _) = params;
+        witness.check_dims(pk.shape.num_voters, pk.shape.num_candidates)?;
+        prove(pk, &witness.votes, &statement.totals)
+    }
+
+    fn verify(
+        params: &Self::Parameters,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+    ) -> Result<bool, ZkError> {
+        verify(&params.1, proof, &statement.totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BallotRule;
+
+    const PLURALITY: BallotRule = BallotRule::ExactlyOne;
+
+    fn shape(num_voters: usize, num_candidates: usize, rule: BallotRule) -> CircuitShape {
+        CircuitShape {
+            num_voters,
+            num_candidates,
+            rule,
+        }
+    }
+
+    #[test]
+    fn test_one_srs_covers_several_elections() {
+        let srs = UniversalSrs::setup(256, &mut OsRng);
+
+        let (pk, vk) = index(&srs, &shape(3, 2, PLURALITY)).unwrap();
+        let votes = vec![vec![1, 0], vec![0, 1], vec![1, 0]];
+        let proof = prove(&pk, &votes, &[2, 1]).unwrap();
+        assert!(verify(&vk, &proof, &[2, 1]).unwrap());
+        assert!(!verify(&vk, &proof, &[3, 0]).unwrap());
+
+        // Different voter and candidate counts, same ceremony
+        let (pk, vk) = index(&srs, &shape(4, 3, BallotRule::AtMostK(2))).unwrap();
+        let votes = vec![vec![1, 1, 0], vec![0, 0, 1], vec![0, 1, 0], vec![1, 0, 1]];
+        let proof = prove(&pk, &votes, &[2, 2, 2]).unwrap();
+        assert!(verify(&vk, &proof, &[2, 2, 2]).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_votes_fail_proving() {
+        let srs = UniversalSrs::setup(64, &mut OsRng);
+        let (pk, _) = index(&srs, &shape(2, 2, PLURALITY)).unwrap();
+
+        let result = prove(&pk, &[vec![1, 1], vec![0, 1]], &[1, 2]);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+
+        let result = prove(&pk, &[vec![2, 0], vec![0, 1]], &[2, 1]);
+        assert!(matches!(result, Err(ZkError::UnsatisfiedWitness)));
+    }
+
+    #[test]
+    fn test_shape_beyond_srs_is_rejected() {
+        let srs = UniversalSrs::setup(16, &mut OsRng);
+
+        assert!(matches!(
+            index(&srs, &shape(20, 3, PLURALITY)),
+            Err(ZkError::InvalidSetup)
+        ));
+    }
+
+    #[test]
+    fn test_srs_round_trip_and_malformed_proof() {
+        let srs = UniversalSrs::setup(64, &mut OsRng);
+        let srs = UniversalSrs::from_bytes(&srs.to_bytes().unwrap()).unwrap();
+
+        let (pk, vk) = index(&srs, &shape(2, 2, PLURALITY)).unwrap();
+        let proof = prove(&pk, &[vec![0, 1], vec![0, 1]], &[0, 2]).unwrap();
+        assert!(verify(&vk, &proof, &[0, 2]).unwrap());
+
+        assert!(matches!(
+            verify(&vk, &[1, 2, 3], &[0, 2]),
+            Err(ZkError::MalformedProof)
+        ));
+    }
+
+    /// Proof for arbitrary totals against a transcript that squeezes `u` before
+    /// the opening proofs: identity commitments, zero evaluations, and openings
+    /// solved for the known `u` so the pairing check balances.
+    fn forge_with_early_u(pk: &UniversalProverKey, totals: &[u64]) -> Vec<u8> {
+        let domain = pk.domain;
+        let omega = domain.group_gen();
+        let public_inputs: Vec<Fr> = totals.iter().map(|&t| Fr::from(t)).collect();
+        let identity = G1Affine::zero();
+        let (wires, z, t) = (vec![identity; 3], identity, vec![identity; 3]);
+        let (evals, z_omega) = (vec![Fr::zero(); 5], Fr::zero());
+
+        let mut transcript = Transcript::new(&pk.vk, &public_inputs).unwrap();
+        transcript.append(&wires).unwrap();
+        transcript.challenge(b"beta");
+        transcript.challenge(b"gamma");
+        transcript.append(&z).unwrap();
+        let alpha = transcript.challenge(b"alpha");
+        transcript.append(&t).unwrap();
+        let zeta = transcript.challenge(b"zeta");
+        transcript.append(&evals).unwrap();
+        transcript.append(&z_omega).unwrap();
+        let v = transcript.challenge(b"v");
+        let u = transcript.challenge(b"u");
+
+        // With everything zeroed, F - E commits to qC + v^4 sigma1 + v^5 sigma2 + r0
+        let lagrange = domain.evaluate_all_lagrange_coefficients(zeta);
+        let pi_zeta: Fr = public_inputs
+            .iter()
+            .zip(&lagrange)
+            .map(|(x, l)| -*x * l)
+            .sum();
+        let r0 = pi_zeta - alpha.square() * lagrange[0];
+        let v4 = v.square().square();
+        let target = &(&(&pk.selectors[4] + &(&pk.sigmas[0] * v4)) + &(&pk.sigmas[1] * (v4 * v)))
+            + &Poly::from_coefficients_vec(vec![r0]);
+
+        // target = (X - zeta) W1 + u (X - zeta omega) W2, with W2 constant
+        let w2 = target.evaluate(&zeta) / (u * (zeta - zeta * omega));
+        let linear = Poly::from_coefficients_vec(vec![-zeta * omega, Fr::one()]);
+        let rest = &target - &(&linear * (u * w2));
+        let w1 = divide_by_linear(&rest, zeta);
+
+        let proof = UniversalProof {
+            wires,
+            z,
+            t,
+            w_zeta: pk.srs.commit(&w1).unwrap(),
+            w_zeta_omega: pk
+                .srs
+                .commit(&Poly::from_coefficients_vec(vec![w2]))
+                .unwrap(),
+            evals,
+            z_omega,
+        };
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_openings_chosen_after_u_are_rejected() {
+        let srs = UniversalSrs::setup(64, &mut OsRng);
+        let (pk, vk) = index(&srs, &shape(2, 2, PLURALITY)).unwrap();
+
+        let forged = forge_with_early_u(&pk, &[5, 0]);
+        assert!(!verify(&vk, &forged, &[5, 0]).unwrap());
+    }
+}
@@ -1,5 +1,5 @@
 use bincode::deserialize;
-use homomorphic::{HomomorphicEncryption, tfhe_uint::TfheU32};
+use homomorphic::{HeContext, HomomorphicEncryption, tfhe_uint::TfheU32};
 use std::io::{Read, Write};
 use std::net::TcpListener;
 use tfhe::FheUint32;
@@ -34,13 +34,12 @@ pub fn run() -> Result<(), TranscipherError> {
                     claimed_sum
                 );
 
-                tfhe::set_server_key(server_key);
-                println!("Server key set on server");
+                let ctx = HeContext::new(server_key);
+                println!("Evaluation context created on server");
 
-                let mut sum_ct = numbers_ct[0].clone();
-                for ct in numbers_ct.iter().skip(1) {
-                    sum_ct = TfheU32::add(&sum_ct, ct).unwrap();
-                }
+                let sum_ct = ctx
+                    .sum::<TfheU32>(numbers_ct[0].clone(), &numbers_ct[1..])
+                    .unwrap();
 
                 let sum = TfheU32::decrypt(&client_key, &sum_ct).unwrap();
                 println!("Decrypted sum on server: {}", sum);
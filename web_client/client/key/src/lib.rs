@@ -1,6 +1,9 @@
 use base64::{engine::general_purpose, Engine as _};
-use bincode;
-use homomorphic::{tfhe_uint::TfheU32, *};
+use homomorphic::public_key::{deserialize_public_key, serialize_ciphertext_list};
+use homomorphic::{
+    tfhe_uint::{TfheU32, TfheU8},
+    *,
+};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
@@ -59,3 +62,30 @@ pub fn encrypt_vote_u32_b64(keys_b64: &str, vote_value: u32) -> Result<String, J
 
     Ok(ct_b64)
 }
+
+/// Encrypts a one-hot ballot under the election's published public key.
+///
+/// `public_key_b64` is the `public_key` field of `GET /elections/{id}/public_key`;
+/// `choice` indexes into the election's candidate list. The result is the
+/// `encrypted_ballot` field for `POST /elections/{id}/ballots/encrypted`.
+#[wasm_bindgen]
+pub fn encrypt_ballot_b64(
+    public_key_b64: &str,
+    choice: u32,
+    num_candidates: u32,
+) -> Result<String, JsValue> {
+    if choice >= num_candidates {
+        return Err(JsValue::from_str("choice is out of range"));
+    }
+
+    let pk_bytes = general_purpose::STANDARD
+        .decode(public_key_b64)
+        .map_err(|e| e.to_string())?;
+    let public_key = deserialize_public_key(&pk_bytes).map_err(|e| e.to_string())?;
+
+    let ballot: Vec<u8> = (0..num_candidates).map(|i| (i == choice) as u8).collect();
+    let list = TfheU8::encrypt_compact(&public_key, &ballot).map_err(|e| e.to_string())?;
+
+    let list_bytes = serialize_ciphertext_list(&list).map_err(|e| e.to_string())?;
+    Ok(general_purpose::STANDARD.encode(list_bytes))
+}
@@ -8,6 +8,17 @@ interface Candidate {
   name: string;
 }
 
+interface Trustee {
+  id: number;
+  name: string;
+  publicKey: string;
+}
+
+interface TrusteeShare {
+  trustee: string;
+  share_url: string;
+}
+
 export default function CreateVote() {
   const navigate = useNavigate();
   const [title, setTitle] = useState("");
@@ -15,12 +26,16 @@ export default function CreateVote() {
   const [startTime, setStartTime] = useState("");
   const [endTime, setEndTime] = useState("");
   const [candidates, setCandidates] = useState<Candidate[]>([{ id: 1, name: "" }]);
+  const [trustees, setTrustees] = useState<Trustee[]>([
+    { id: 1, name: "", publicKey: "" },
+  ]);
+  const [threshold, setThreshold] = useState(1);
   const [loading, setLoading] = useState(false);
   const [success, setSuccess] = useState(false);
   const [error, setError] = useState("");
   const [electionId, setElectionId] = useState<string | null>(null);
   const [serverKey, setServerKey] = useState<string | null>(null);
-  const [clientKey, setClientKey] = useState<string | null>(null);
+  const [trusteeShares, setTrusteeShares] = useState<TrusteeShare[]>([]);
 
   const addCandidate = () => {
     setCandidates((prev) => [...prev, { id: prev.length + 1, name: "" }]);
@@ -36,11 +51,37 @@ export default function CreateVote() {
     );
   };
 
+  const addTrustee = () => {
+    setTrustees((prev) => [
+      ...prev,
+      { id: Math.max(...prev.map((t) => t.id)) + 1, name: "", publicKey: "" },
+    ]);
+  };
+
+  const removeTrustee = (id: number) => {
+    setTrustees((prev) => prev.filter((t) => t.id !== id));
+    setThreshold((prev) => Math.min(prev, trustees.length - 1));
+  };
+
+  const updateTrustee = (id: number, field: "name" | "publicKey", value: string) => {
+    setTrustees((prev) =>
+      prev.map((t) => (t.id === id ? { ...t, [field]: value } : t))
+    );
+  };
+
   const handleCreate = async () => {
     if (!title.trim() || candidates.some((c) => !c.name.trim())) {
       alert("Please fill in all required fields");
       return;
     }
+    if (trustees.some((t) => !t.name.trim() || !t.publicKey.trim())) {
+      alert("Every trustee needs a name and the public key from `tally trustee-key`");
+      return;
+    }
+    if (threshold < 1 || threshold > trustees.length) {
+      alert(`The threshold must be between 1 and ${trustees.length}`);
+      return;
+    }
     if (!endTime) {
       alert("Please set an end time for the election");
       return;
@@ -61,7 +102,7 @@ export default function CreateVote() {
     setError("");
     setElectionId(null);
     setServerKey(null);
-    setClientKey(null);
+    setTrusteeShares([]);
 
     try {
       const res = await axios.post("http://localhost:8080/admin/elections", {
@@ -69,13 +110,21 @@ export default function CreateVote() {
         start_time: startTimestamp,
         end_time: endTimestamp,
         candidates: candidates.map((c, i) => ({ id: i + 1, name: c.name })),
+        trustees: trustees.map((t) => ({
+          name: t.name.trim(),
+          public_key: t.publicKey.trim(),
+        })),
+        threshold,
       });
 
-      const { election_id, server_key, client_key } = res.data;
+      const { election_id, server_key, trustee_shares } = res.data;
+
+      // New elections start as drafts; open this one for voting right away
+      await axios.post(`http://localhost:8080/admin/elections/${election_id}/open`);
 
       setElectionId(election_id);
       setServerKey(server_key);
-      setClientKey(client_key);
+      setTrusteeShares(trustee_shares);
       setSuccess(true);
     } catch (err) {
       console.error(err);
@@ -180,6 +229,65 @@ export default function CreateVote() {
             </button>
           </div>
 
+          <div>
+            <label className="block text-gray-400 mb-2 text-sm">Trustees</label>
+            <p className="text-gray-500 text-xs mb-2">
+              Each trustee runs <code>tally trustee-key</code> and sends you only the
+              public key it prints. Their key share is sealed to it.
+            </p>
+            <div className="space-y-3">
+              {trustees.map((t) => (
+                <div
+                  key={t.id}
+                  className="flex items-center gap-2 bg-zinc-800 rounded-lg px-4 py-2 border border-zinc-700"
+                >
+                  <input
+                    type="text"
+                    value={t.name}
+                    onChange={(e) => updateTrustee(t.id, "name", e.target.value)}
+                    placeholder="Trustee name"
+                    className="w-1/3 bg-transparent outline-none text-gray-100 placeholder-gray-500"
+                  />
+                  <input
+                    type="text"
+                    value={t.publicKey}
+                    onChange={(e) => updateTrustee(t.id, "publicKey", e.target.value)}
+                    placeholder="Public key (hex)"
+                    className="flex-1 bg-transparent outline-none font-mono text-xs text-gray-100 placeholder-gray-500"
+                  />
+                  {trustees.length > 1 && (
+                    <button
+                      onClick={() => removeTrustee(t.id)}
+                      className="text-red-400 hover:text-red-500 transition"
+                    >
+                      ✕
+                    </button>
+                  )}
+                </div>
+              ))}
+            </div>
+
+            <div className="mt-3 flex items-center gap-4">
+              <button
+                onClick={addTrustee}
+                className="px-4 py-2 rounded-lg bg-cyan-700 hover:bg-cyan-600 text-white font-medium shadow-md transition-transform hover:scale-105"
+              >
+                + Add Trustee
+              </button>
+              <label className="text-gray-400 text-sm">
+                Trustees needed to tally:{" "}
+                <input
+                  type="number"
+                  min={1}
+                  max={trustees.length}
+                  value={threshold}
+                  onChange={(e) => setThreshold(Number(e.target.value))}
+                  className="w-16 bg-zinc-800 text-gray-100 px-2 py-1 rounded-lg border border-zinc-700"
+                />
+              </label>
+            </div>
+          </div>
+
           <div className="pt-6">
             <button
               onClick={handleCreate}
@@ -213,12 +321,23 @@ export default function CreateVote() {
                 </div>
               )}
 
-              {clientKey && (
+              {trusteeShares.length > 0 && (
                 <div className="text-left bg-zinc-800 p-3 rounded-lg border border-zinc-700 overflow-x-auto text-xs">
-                  <p className="text-purple-400 mb-1 font-medium">Client Key:</p>
-                  <pre className="text-gray-300 whitespace-pre-wrap wrap-break-word">
-                    {clientKey}
-                  </pre>
+                  <p className="text-purple-400 mb-1 font-medium">Trustee Shares:</p>
+                  <p className="text-gray-400 mb-2">
+                    Send each trustee their own link. The share behind it is sealed to
+                    their public key; they open it with <code>tally open-share</code>.
+                  </p>
+                  <ul className="space-y-1">
+                    {trusteeShares.map((s) => (
+                      <li key={s.share_url} className="text-gray-300">
+                        <span className="text-cyan-400">{s.trustee}</span>:{" "}
+                        <span className="font-mono wrap-break-word">
+                          http://localhost:8080{s.share_url}
+                        </span>
+                      </li>
+                    ))}
+                  </ul>
                 </div>
               )}
 
@@ -2,6 +2,7 @@ import { useEffect, useState } from "react";
 import { motion } from "framer-motion";
 import { useParams } from "react-router-dom";
 import axios from "axios";
+import { encryptBallot, initWasm } from "../utils/wasmKeys";
 
 interface Candidate {
   id: number;
@@ -59,11 +60,19 @@ export default function ElectionDetail() {
     setSuccess(false);
 
     try {
-      await axios.post(`http://localhost:8080/elections/${election.id}/ballots`, {
+      // The vote is encrypted here; the server only ever sees the ciphertexts
+      await initWasm();
+      const res = await axios.get(`http://localhost:8080/elections/${election.id}/public_key`);
+      const choice = election.candidates.findIndex((c) => c.id === selected);
+      const encrypted_ballot = await encryptBallot(
+        res.data.public_key,
+        choice,
+        election.candidates.length,
+      );
+      await axios.post(`http://localhost:8080/elections/${election.id}/ballots/encrypted`, {
         token,
-        candidate_id: selected,
+        encrypted_ballot,
       });
-      console.log("Voted:", selected);
 
       setSuccess(true);
       setSelected(null);
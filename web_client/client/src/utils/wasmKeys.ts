@@ -1,4 +1,9 @@
-import init, { generate_keys_b64, extract_server_key_b64, encrypt_vote_u32_b64 } from "../../pkg/key.js";
+import init, {
+  generate_keys_b64,
+  extract_server_key_b64,
+  encrypt_vote_u32_b64,
+  encrypt_ballot_b64,
+} from "../../pkg/key.js";
 
 export async function initWasm() {
   await init();
@@ -29,3 +34,11 @@ export async function getOrCreateKeys(electionId: string) {
 export async function encryptVote(keysB64: string, candidateId: number) {
   return await encrypt_vote_u32_b64(keysB64, candidateId);
 }
+
+/**
+ * Encrypt a one-hot ballot for candidate index `choice` under the election's
+ * public key, ready to post to `/elections/{id}/ballots/encrypted`.
+ */
+export async function encryptBallot(publicKeyB64: string, choice: number, numCandidates: number) {
+  return await encrypt_ballot_b64(publicKeyB64, choice, numCandidates);
+}
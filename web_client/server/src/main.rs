@@ -5,9 +5,11 @@ use actix_web::{
     web::{self},
 };
 
+mod bulletin;
 mod db;
 mod models;
 mod routes;
+use bulletin::BulletinSigner;
 use db::Database;
 
 use crate::routes::{auth, election, key};
@@ -16,6 +18,7 @@ use crate::routes::{auth, election, key};
 async fn main() -> std::io::Result<()> {
     env_logger::init();
     let db = Database::init("./vote_db");
+    let signer = web::Data::new(BulletinSigner::load_or_create("keys/bulletin_signing.key")?);
 
     HttpServer::new(move || {
         App::new()
@@ -27,6 +30,7 @@ async fn main() -> std::io::Result<()> {
                     .allow_any_header(),
             )
             .app_data(web::Data::new(db.clone()))
+            .app_data(signer.clone())
             .service(auth::routes())
             .service(election::routes())
             .service(key::routes())
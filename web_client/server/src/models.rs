@@ -15,6 +15,35 @@ pub struct Candidate {
     pub label: String,
 }
 
+/// Lifecycle of an election. States only move forward, one step at a time.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ElectionState {
+    /// Created, not yet accepting ballots.
+    #[default]
+    Draft,
+    /// Accepting ballots between `start_time` and `end_time`.
+    Open,
+    /// No more ballots; ready to tally.
+    Closed,
+    /// Encrypted tally computed, awaiting the trustees.
+    Tallied,
+    /// Decrypted result and its proof published.
+    Published,
+}
+
+impl ElectionState {
+    pub fn next(self) -> Option<Self> {
+        match self {
+            ElectionState::Draft => Some(ElectionState::Open),
+            ElectionState::Open => Some(ElectionState::Closed),
+            ElectionState::Closed => Some(ElectionState::Tallied),
+            ElectionState::Tallied => Some(ElectionState::Published),
+            ElectionState::Published => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Election {
     pub id: String,
@@ -22,13 +51,66 @@ pub struct Election {
     pub start_time: u64,
     pub end_time: u64,
     pub candidates: Vec<Candidate>,
+    /// Kept in step with `state` for clients that only read this flag.
     pub closed: bool,
+    #[serde(default)]
+    pub state: ElectionState,
+}
+
+impl Election {
+    /// Moves to `to` if it is the next state, otherwise explains why not.
+    pub fn transition(&mut self, to: ElectionState) -> Result<(), String> {
+        if self.state.next() != Some(to) {
+            return Err(format!(
+                "Cannot move election from {:?} to {:?}",
+                self.state, to
+            ));
+        }
+        self.state = to;
+        self.closed = to != ElectionState::Draft && to != ElectionState::Open;
+        Ok(())
+    }
+
+    /// Whether a ballot cast at `now` falls inside the voting window.
+    pub fn accepts_ballots(&self, now: u64) -> Result<(), String> {
+        if self.state != ElectionState::Open {
+            return Err(format!("Election is {:?}, not open", self.state));
+        }
+        if now < self.start_time || now >= self.end_time {
+            return Err("Outside the voting window".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A holder of a client key share, and the X25519 key their share is sealed to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Trustee {
+    pub name: String,
+    /// Hex X25519 public key, from `tally trustee-key`.
+    pub public_key: String,
+}
+
+/// A trustee's share as the server keeps it: sealed, so only the trustee can open it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SealedShare {
+    pub trustee: String,
+    /// Base64 `KeyShare::seal` output.
+    pub sealed_share: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ElectionKeys {
     pub id: String,
     pub server: String,
+    #[serde(default)]
+    pub public: String,
+    /// Holders of the client key shares; the server only keeps them sealed.
+    #[serde(default)]
+    pub trustees: Vec<Trustee>,
+    /// Number of trustees needed to rebuild the client key.
+    #[serde(default)]
+    pub threshold: u8,
     pub timestamp: u64,
 }
 
@@ -1,17 +1,27 @@
 use actix_web::{HttpResponse, Scope, get, post, web};
-use homomorphic::{FheDecrypt, FheEncrypt, FheTrivialEncrypt};
+use base64::{Engine as _, engine::general_purpose};
+use homomorphic::ballot::EncryptedTally;
+use homomorphic::custody::split_client_key;
+use homomorphic::public_key::{deserialize_ciphertext_list, serialize_public_key};
+use homomorphic::tfhe_uint::TfheU8;
+use homomorphic::{HeContext, PublicKeyEncryption};
+use serde::Deserialize;
 use serde_json::json;
 use sha2::{Digest, Sha256};
 use std::{
     fs,
     time::{SystemTime, UNIX_EPOCH},
 };
-use tfhe::{ConfigBuilder, ServerKey, generate_keys, set_server_key};
+use tfhe::{ConfigBuilder, ServerKey, generate_keys};
 use uuid::Uuid;
+use zk::merkle::InclusionReceipt;
 
 use crate::{
+    bulletin::{self, BulletinSigner},
     db::Database,
-    models::{Ballot, Candidate, Election, ElectionKeys, TokenRecord},
+    models::{
+        Ballot, Candidate, Election, ElectionKeys, ElectionState, SealedShare, TokenRecord, Trustee,
+    },
 };
 use std::path::Path;
 use tfhe::{ClientKey, FheUint8};
@@ -69,6 +79,11 @@ async fn create_election(
     let candidates: Vec<Candidate> =
         serde_json::from_value(body["candidates"].clone()).unwrap_or_default();
 
+    let (trustees, threshold) = match parse_trustees(&body) {
+        Ok(t) => t,
+        Err(resp) => return resp,
+    };
+
     let election = Election {
         id: id.clone(),
         name,
@@ -76,13 +91,14 @@ async fn create_election(
         end_time,
         candidates,
         closed: false,
+        state: ElectionState::Draft,
     };
 
     // --- Step 2: Store election ---
     let serialized = serde_json::to_vec(&election).unwrap();
     db.put(&format!("elections:{}", id), &serialized);
 
-    // --- Step 3: Generate FHE keys (directly inside this function) ---
+    // --- Step 3: Generate FHE keys; the client key only leaves as trustee shares ---
     let key_path = format!("keys:{}", id);
     if db.exists(&key_path) {
         return HttpResponse::Conflict().json(json!({ "error": "Keys already exist" }));
@@ -90,21 +106,29 @@ async fn create_election(
 
     let config = ConfigBuilder::default().build();
     let (client_key, server_key) = generate_keys(config);
-    let client_bytes = bincode::serialize(&client_key).unwrap();
+    let public_key = TfheU8::public_keygen(&client_key).unwrap();
+    if let Err(resp) = seal_trustee_shares(&db, &id, &client_key, &trustees, threshold) {
+        return resp;
+    }
+    drop(client_key);
     let server_bytes = bincode::serialize(&server_key).unwrap();
+    let public_bytes = serialize_public_key(&public_key).unwrap();
     let key_dir = Path::new("keys");
     if !key_dir.exists() {
         fs::create_dir_all(key_dir).expect("Failed to create keys directory");
     }
-    let client_path = format!("keys/{}_client.key", id);
     let server_path = format!("keys/{}_server.key", id);
+    let public_path = format!("keys/{}_public.key", id);
 
-    fs::write(&client_path, &client_bytes).expect("Failed to write client key");
     fs::write(&server_path, &server_bytes).expect("Failed to write server key");
+    fs::write(&public_path, &public_bytes).expect("Failed to write public key");
 
     let record = ElectionKeys {
         id: id.clone(),
         server: server_path.clone(),
+        public: public_path.clone(),
+        trustees,
+        threshold,
         timestamp: SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -113,27 +137,177 @@ async fn create_election(
 
     db.put(&key_path, &serde_json::to_vec(&record).unwrap());
 
-    // --- Step 4: Return election id and keys together ---
+    // --- Step 4: Return election id, key paths and where each trustee fetches their share ---
     HttpResponse::Ok().json(json!({
         "election_id": id,
-        "client_key": client_path,
-        "server_key": server_path
+        "server_key": server_path,
+        "public_key": public_path,
+        "threshold": threshold,
+        "trustee_shares": share_urls(&id, &record.trustees)
     }))
 }
 
+/// Reads `trustees` (`[{ "name": ..., "public_key": <hex X25519 key> }]`) and
+/// `threshold` from an election request. The threshold defaults to two, or one
+/// for a single trustee.
+pub(crate) fn parse_trustees(body: &serde_json::Value) -> Result<(Vec<Trustee>, u8), HttpResponse> {
+    let Ok(trustees) = serde_json::from_value::<Vec<Trustee>>(body["trustees"].clone()) else {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": "trustees must list each trustee's name and public_key"
+        })));
+    };
+    let threshold = body["threshold"]
+        .as_u64()
+        .unwrap_or(2.min(trustees.len() as u64));
+
+    if trustees.is_empty() || trustees.len() > u8::MAX as usize {
+        return Err(HttpResponse::BadRequest()
+            .json(json!({ "error": "An election needs between 1 and 255 trustees" })));
+    }
+    if threshold == 0 || threshold > trustees.len() as u64 {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": format!("Threshold must be between 1 and {}", trustees.len())
+        })));
+    }
+    for (i, trustee) in trustees.iter().enumerate() {
+        if trustees[..i].iter().any(|t| t.name == trustee.name) {
+            return Err(HttpResponse::BadRequest()
+                .json(json!({ "error": format!("Trustee {} is listed twice", trustee.name) })));
+        }
+        if trustee_public_key(trustee).is_none() {
+            return Err(HttpResponse::BadRequest().json(json!({
+                "error": format!("Trustee {} has no valid public_key", trustee.name)
+            })));
+        }
+    }
+
+    Ok((trustees, threshold as u8))
+}
+
+fn trustee_public_key(trustee: &Trustee) -> Option<[u8; 32]> {
+    hex::decode(&trustee.public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+}
+
+/// Splits the client key, seals each share to its trustee's public key and stores
+/// the sealed shares for the trustees to fetch one by one.
+pub(crate) fn seal_trustee_shares(
+    db: &Database,
+    election_id: &str,
+    client_key: &ClientKey,
+    trustees: &[Trustee],
+    threshold: u8,
+) -> Result<(), HttpResponse> {
+    let internal = |_| {
+        HttpResponse::InternalServerError().json(json!({ "error": "Failed to seal key shares" }))
+    };
+
+    let shares = split_client_key(client_key, threshold, trustees.len() as u8).map_err(internal)?;
+    let sealed = trustees
+        .iter()
+        .zip(shares)
+        .map(|(trustee, share)| {
+            // Checked by `parse_trustees`
+            let public_key = trustee_public_key(trustee).unwrap();
+            let bytes = share.seal(&public_key).map_err(internal)?;
+            Ok(SealedShare {
+                trustee: trustee.name.clone(),
+                sealed_share: general_purpose::STANDARD.encode(bytes),
+            })
+        })
+        .collect::<Result<Vec<_>, HttpResponse>>()?;
+
+    db.put(
+        &format!("trustee_shares:{}", election_id),
+        &serde_json::to_vec(&sealed).unwrap(),
+    );
+    Ok(())
+}
+
+/// Where each trustee fetches their sealed share, see [`get_trustee_share`].
+pub(crate) fn share_urls(election_id: &str, trustees: &[Trustee]) -> Vec<serde_json::Value> {
+    trustees
+        .iter()
+        .enumerate()
+        .map(|(i, trustee)| {
+            json!({
+                "trustee": trustee.name,
+                "share_url": format!("/elections/{}/trustees/{}/share", election_id, i)
+            })
+        })
+        .collect()
+}
+
+/// One trustee's share, sealed to their public key. Open it with
+/// `tally open-share`; nobody else, the server included, can.
+#[get("/elections/{id}/trustees/{index}/share")]
+async fn get_trustee_share(
+    db: web::Data<Database>,
+    path: web::Path<(String, usize)>,
+) -> HttpResponse {
+    let (election_id, index) = path.into_inner();
+    let shares: Vec<SealedShare> = match db.get(&format!("trustee_shares:{}", election_id)) {
+        Some(bytes) => serde_json::from_slice(&bytes).unwrap(),
+        None => {
+            return HttpResponse::NotFound().json(json!({ "error": "Election has no key shares" }));
+        }
+    };
+
+    match shares.get(index) {
+        Some(share) => HttpResponse::Ok().json(json!({
+            "election_id": election_id,
+            "trustee": share.trustee,
+            "sealed_share": share.sealed_share
+        })),
+        None => HttpResponse::NotFound().json(json!({ "error": "Trustee not found" })),
+    }
+}
+
+/// Starts accepting ballots (draft -> open).
+#[post("/admin/elections/{id}/open")]
+async fn open_election(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
+    advance_election(&db, &path.into_inner(), ElectionState::Open)
+}
+
+/// Stops accepting ballots (open -> closed).
 #[post("/admin/elections/{id}/close")]
 async fn close_election(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
-    let id = path.into_inner();
-    let key = format!("elections:{}", id);
+    advance_election(&db, &path.into_inner(), ElectionState::Closed)
+}
 
-    if let Some(bytes) = db.get(&key) {
-        let mut election: Election = serde_json::from_slice(&bytes).unwrap();
-        election.closed = true;
-        db.put(&key, &serde_json::to_vec(&election).unwrap());
-        HttpResponse::Ok().json(json!({ "status": "closed" }))
-    } else {
-        HttpResponse::NotFound().json(json!({ "error": "Election not found" }))
+fn load_election(db: &Database, id: &str) -> Result<Election, HttpResponse> {
+    let bytes = db
+        .get(&format!("elections:{}", id))
+        .ok_or_else(|| HttpResponse::NotFound().json(json!({ "error": "Election not found" })))?;
+    Ok(serde_json::from_slice(&bytes).unwrap())
+}
+
+fn save_election(db: &Database, election: &Election) {
+    db.put(
+        &format!("elections:{}", election.id),
+        &serde_json::to_vec(election).unwrap(),
+    );
+}
+
+/// Applies a state transition with no other side effects.
+fn advance_election(db: &Database, id: &str, to: ElectionState) -> HttpResponse {
+    let mut election = match load_election(db, id) {
+        Ok(election) => election,
+        Err(resp) => return resp,
+    };
+    if let Err(e) = election.transition(to) {
+        return HttpResponse::Conflict().json(json!({ "error": e }));
     }
+    save_election(db, &election);
+    HttpResponse::Ok().json(json!({ "status": election.state }))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 #[get("/elections")]
@@ -160,8 +334,35 @@ async fn get_election(db: web::Data<Database>, path: web::Path<String>) -> HttpR
     }
 }
 
-#[post("/elections/{id}/ballots")]
-async fn submit_ballot(
+/// Published compact public key voters encrypt their ballots under.
+#[get("/elections/{id}/public_key")]
+async fn get_public_key(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
+    let id = path.into_inner();
+    if !db.exists(&format!("elections:{}", id)) {
+        return HttpResponse::NotFound().json(json!({ "error": "Election not found" }));
+    }
+
+    match fs::read(format!("keys/{}_public.key", id)) {
+        Ok(bytes) => HttpResponse::Ok().json(json!({
+            "election_id": id,
+            "public_key": general_purpose::STANDARD.encode(bytes)
+        })),
+        Err(_) => {
+            HttpResponse::InternalServerError().json(json!({ "error": "Public key missing" }))
+        }
+    }
+}
+
+/// Accepts a ballot the voter encrypted themselves under the election's public key.
+///
+/// Body: `{ "token": ..., "encrypted_ballot": <base64 compact ciphertext list> }`, one
+/// encrypted `u8` per entry of `Election::candidates`, in the same order, as produced by
+/// `encrypt_ballot_b64` in the WASM client. The server checks the shape and expands the
+/// list; it never sees the vote. Whether the ballot is one-hot is only known encrypted:
+/// the tally folds it in through `EncryptedTally`, which counts a malformed ballot as
+/// rejected instead of adding it to any candidate.
+#[post("/elections/{id}/ballots/encrypted")]
+async fn submit_encrypted_ballot(
     db: web::Data<Database>,
     path: web::Path<String>,
     body: web::Json<serde_json::Value>,
@@ -173,41 +374,67 @@ async fn submit_ballot(
         return HttpResponse::NotFound().json(json!({ "error": "Election not found" }));
     };
     let election: Election = serde_json::from_slice(&bytes).unwrap();
+    if let Err(e) = election.accepts_ballots(now()) {
+        return HttpResponse::Forbidden().json(json!({ "error": e }));
+    }
 
-    let chosen_id = body["candidate_id"].as_u64().unwrap_or(0) as u32;
-    println!("Vote for candidate ID: {}", chosen_id);
-
-    let client_path = format!("keys/{}_client.key", election_id);
-    let client_bytes = match fs::read(&client_path) {
-        Ok(b) => b,
-        Err(_) => {
-            return HttpResponse::InternalServerError()
-                .json(json!({ "error": "Client key missing" }));
-        }
+    // --- Decode and shape-check the ciphertexts ---
+    let Some(ballot_b64) = body["encrypted_ballot"].as_str() else {
+        return HttpResponse::BadRequest().json(json!({ "error": "Missing encrypted_ballot" }));
+    };
+    let Ok(list_bytes) = general_purpose::STANDARD.decode(ballot_b64) else {
+        return HttpResponse::BadRequest()
+            .json(json!({ "error": "encrypted_ballot is not valid base64" }));
     };
-    let client_key: ClientKey = bincode::deserialize(&client_bytes)
-        .map_err(|_| HttpResponse::InternalServerError().json(json!({ "error": "Bad client key" })))
-        .unwrap();
-
-    let mut encrypted_vec = Vec::new();
-    for c in &election.candidates {
-        let bit: u8 = if c.id == chosen_id { 1 } else { 0 };
-        encrypted_vec.push((c.id, FheUint8::encrypt(bit, &client_key)));
+    let Ok(list) = deserialize_ciphertext_list(&list_bytes) else {
+        return HttpResponse::BadRequest().json(json!({ "error": "Malformed ciphertext list" }));
+    };
+    if list.len() != election.candidates.len() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!(
+                "Ballot has {} entries but the election has {} candidates",
+                list.len(),
+                election.candidates.len()
+            )
+        }));
     }
 
-    let token = body["token"].as_str().unwrap_or("").to_string();
+    let ctx = match load_server_context(&election_id) {
+        Ok(ctx) => ctx,
+        Err(resp) => return resp,
+    };
+    let Ok(cts) = ctx.run(|| TfheU8::expand(&list)) else {
+        return HttpResponse::BadRequest()
+            .json(json!({ "error": "Ballot entries must be encrypted u8 values" }));
+    };
+
+    let token_hash = match consume_token(&db, body["token"].as_str().unwrap_or("")) {
+        Ok(hash) => hash,
+        Err(resp) => return resp,
+    };
+
+    let encrypted_vec = election.candidates.iter().map(|c| c.id).zip(cts).collect();
+    let ballot_id = store_ballot(&db, &election_id, encrypted_vec, token_hash);
+    HttpResponse::Ok().json(json!({
+        "ballot_id": ballot_id,
+        "receipt": ballot_receipt(&db, &election_id, &ballot_id)
+    }))
+}
+
+/// Marks a voter token as used, returning its hash.
+fn consume_token(db: &Database, token: &str) -> Result<String, HttpResponse> {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     let token_hash = format!("{:x}", hasher.finalize());
     let token_key = format!("tokens:{}", token_hash);
 
     let Some(bytes) = db.get(&token_key) else {
-        return HttpResponse::Unauthorized().json(json!({ "error": "Invalid token" }));
+        return Err(HttpResponse::Unauthorized().json(json!({ "error": "Invalid token" })));
     };
     let mut record: TokenRecord = serde_json::from_slice(&bytes).unwrap();
 
     if record.used {
-        return HttpResponse::Forbidden().json(json!({ "error": "Token already used" }));
+        return Err(HttpResponse::Forbidden().json(json!({ "error": "Token already used" })));
     }
 
     record.used = true;
@@ -219,15 +446,23 @@ async fn submit_ballot(
     );
     db.put(&token_key, &serde_json::to_vec(&record).unwrap());
 
+    Ok(token_hash)
+}
+
+/// Stores an encrypted ballot, appends it to the bulletin board and returns its id.
+fn store_ballot(
+    db: &Database,
+    election_id: &str,
+    encrypted_vector: Vec<(u32, FheUint8)>,
+    token_hash: String,
+) -> String {
     let ballot_id = Uuid::new_v4().to_string();
+    let ciphertext = bincode::serialize(&encrypted_vector).unwrap();
     let ballot = Ballot {
         ballot_id: ballot_id.clone(),
-        election_id: election_id.clone(),
-        encrypted_vector: encrypted_vec,
-        timestamp: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
+        election_id: election_id.to_string(),
+        encrypted_vector,
+        timestamp: now(),
         token_hash,
     };
 
@@ -235,110 +470,634 @@ async fn submit_ballot(
         &format!("ballots:{}", ballot_id),
         &bincode::serialize(&ballot).unwrap(),
     );
-    println!("Doneee");
-    HttpResponse::Ok().json(json!({ "ballot_id": ballot_id }))
+    bulletin::append(db, election_id, &ballot_id, &ciphertext, ballot.timestamp);
+    ballot_id
 }
 
-#[get("/elections/{id}/result")]
-async fn calculate_winner(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
-    let election_id = path.into_inner();
-    let election_key = format!("elections:{}", election_id);
-
-    // --- Load election ---
-    let Some(election_bytes) = db.get(&election_key) else {
-        return HttpResponse::NotFound().json(json!({ "error": "Election not found" }));
-    };
-    let election: Election = serde_json::from_slice(&election_bytes).unwrap();
+/// Inclusion receipt for a ballot against the Merkle root of its election's
+/// bulletin board as it stands now.
+fn ballot_receipt(db: &Database, election_id: &str, ballot_id: &str) -> Option<InclusionReceipt> {
+    let entries = bulletin::entries(db, election_id, 0, u64::MAX);
+    let index = entries
+        .iter()
+        .position(|entry| entry.ballot_id == ballot_id)?;
+    let leaves = entries
+        .iter()
+        .map(|entry| zk::merkle::decode_hash(&entry.ciphertext_hash))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    InclusionReceipt::new(ballot_id, &leaves, index)
+}
 
-    // --- Load server & client keys ---
+/// Loads the election's server key into an evaluation context.
+fn load_server_context(election_id: &str) -> Result<HeContext, HttpResponse> {
     let server_path = format!("keys/{}_server.key", election_id);
-    let client_path = format!("keys/{}_client.key", election_id);
+    let server_bytes = fs::read(&server_path).map_err(|_| {
+        HttpResponse::InternalServerError().json(json!({ "error": "Server key missing" }))
+    })?;
+    let server_key: ServerKey = bincode::deserialize(&server_bytes).map_err(|_| {
+        HttpResponse::InternalServerError().json(json!({ "error": "Bad server key" }))
+    })?;
+    Ok(HeContext::new(server_key))
+}
 
-    let server_bytes = match fs::read(&server_path) {
-        Ok(b) => b,
-        Err(_) => {
-            return HttpResponse::InternalServerError()
-                .json(json!({ "error": "Server key missing" }));
-        }
-    };
-    let client_bytes = match fs::read(&client_path) {
-        Ok(b) => b,
-        Err(_) => {
-            return HttpResponse::InternalServerError()
-                .json(json!({ "error": "Client key missing" }));
-        }
+/// Computes the encrypted tally of a closed election (closed -> tallied).
+#[post("/admin/elections/{id}/tally")]
+async fn tally_election(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
+    let election_id = path.into_inner();
+    let mut election = match load_election(&db, &election_id) {
+        Ok(election) => election,
+        Err(resp) => return resp,
     };
+    if election.state != ElectionState::Closed {
+        return HttpResponse::Conflict().json(json!({
+            "error": format!("Election is {:?}; only closed elections can be tallied", election.state)
+        }));
+    }
 
-    let server_key: ServerKey = bincode::deserialize(&server_bytes).unwrap();
-    let client_key: ClientKey = bincode::deserialize(&client_bytes).unwrap();
-    set_server_key(server_key);
+    // --- Load server key ---
+    let ctx = match load_server_context(&election_id) {
+        Ok(ctx) => ctx,
+        Err(resp) => return resp,
+    };
 
     // --- Gather ballots ---
-    let mut ballots: Vec<Ballot> = Vec::new();
-    for (_k, v) in db.scan_prefix("ballots:") {
-        if let Ok(ballot) = bincode::deserialize::<Ballot>(&v) {
-            if ballot.election_id == election_id {
-                ballots.push(ballot);
+    let ballots = match election_ballots(&db, &election_id) {
+        Ok(ballots) => ballots,
+        Err(resp) => return resp,
+    };
+    if ballots.is_empty() {
+        return HttpResponse::Conflict().json(json!({ "error": "No ballots found" }));
+    }
+
+    // --- Homomorphically add encrypted tallies ---
+    // Each ballot is checked for one-hot form under encryption; malformed ones add zero
+    // and are only visible through the final rejected count
+    let num_candidates = election.candidates.len();
+    let tally = ctx.run(|| {
+        let mut tally = EncryptedTally::new(num_candidates);
+        for (ballot, _) in &ballots {
+            let entries: Vec<FheUint8> = ballot
+                .encrypted_vector
+                .iter()
+                .map(|(_cid, ct)| ct.clone())
+                .collect();
+            // Shape was checked on submission; a mismatch here means a corrupted record
+            if tally.add_ballot(&entries).is_err() {
+                tally.rejected += 1u32;
             }
         }
+        tally
+    });
+
+    // Kept so the published result can be checked against this exact aggregate
+    let tally_bytes = bincode::serialize(&tally).unwrap();
+    db.put(&format!("tallies:{}", election_id), &tally_bytes);
+
+    election.transition(ElectionState::Tallied).unwrap();
+    save_election(&db, &election);
+
+    HttpResponse::Ok().json(json!({
+        "status": election.state,
+        "aggregate_hash": hex_sha256(&tally_bytes)
+    }))
+}
+
+/// Inclusion receipt for a ballot against the current Merkle root over the
+/// election's ballots.
+///
+/// Once the election is closed the root no longer changes, and it is the ballot
+/// set digest the published tally bulletin commits to.
+#[get("/elections/{id}/ballots/{ballot_id}/proof")]
+async fn get_ballot_proof(
+    db: web::Data<Database>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (election_id, ballot_id) = path.into_inner();
+    let election = match load_election(&db, &election_id) {
+        Ok(election) => election,
+        Err(resp) => return resp,
+    };
+
+    match ballot_receipt(&db, &election_id, &ballot_id) {
+        Some(receipt) => HttpResponse::Ok().json(json!({
+            "election_id": election_id,
+            "state": election.state,
+            "receipt": receipt
+        })),
+        None => HttpResponse::NotFound().json(json!({ "error": "Ballot not found" })),
     }
+}
 
-    if ballots.is_empty() {
-        return HttpResponse::Ok().json(json!({ "message": "No ballots found" }));
+#[derive(Deserialize)]
+struct BulletinQuery {
+    #[serde(default)]
+    offset: u64,
+    #[serde(default = "default_bulletin_limit")]
+    limit: u64,
+}
+
+fn default_bulletin_limit() -> u64 {
+    100
+}
+
+/// Page of the election's bulletin board with the current signed head.
+///
+/// Query: `offset` (default 0) and `limit` (default 100, at most 1000). Entries are
+/// in append order; replaying them all from `bulletin::genesis` must end at `head`.
+#[get("/elections/{id}/bulletin")]
+async fn get_bulletin(
+    db: web::Data<Database>,
+    signer: web::Data<BulletinSigner>,
+    path: web::Path<String>,
+    query: web::Query<BulletinQuery>,
+) -> HttpResponse {
+    let election_id = path.into_inner();
+    if let Err(resp) = load_election(&db, &election_id) {
+        return resp;
     }
 
-    // --- Homomorphically add encrypted tallies ---
-    let num_candidates = election.candidates.len();
-    let mut totals: Vec<FheUint8> = vec![FheUint8::encrypt_trivial(0u8); num_candidates];
+    let limit = query.limit.min(1000);
+    let entries = bulletin::entries(&db, &election_id, query.offset, limit);
+    let head = signer.sign(bulletin::head(&db, &election_id));
 
-    for ballot in ballots {
-        for (i, (_cid, vote_cipher)) in ballot.encrypted_vector.iter().enumerate() {
-            totals[i] = &totals[i] + vote_cipher;
+    HttpResponse::Ok().json(json!({
+        "election_id": election_id,
+        "offset": query.offset,
+        "limit": limit,
+        "entries": entries,
+        "head": head
+    }))
+}
+
+/// Published result once the trustees have posted one, otherwise the encrypted
+/// tally for them to decrypt with the `tally` tool.
+///
+/// Before publication the response carries every counted ballot (id, SHA-256 and
+/// ciphertexts, in tally order) and the aggregated ciphertexts with their hash.
+/// The published result keeps the ballot hashes, so voters can check their
+/// receipts against the ballot set that was counted.
+#[get("/elections/{id}/result")]
+async fn calculate_winner(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
+    let election_id = path.into_inner();
+    let election = match load_election(&db, &election_id) {
+        Ok(election) => election,
+        Err(resp) => return resp,
+    };
+
+    match election.state {
+        ElectionState::Published => {
+            let result = db
+                .get(&format!("results:{}", election_id))
+                .unwrap_or_default();
+            return HttpResponse::Ok()
+                .content_type("application/json")
+                .body(result);
+        }
+        ElectionState::Tallied => {}
+        state => {
+            return HttpResponse::Conflict().json(json!({
+                "error": format!("Election is {:?}; no tally yet", state)
+            }));
         }
     }
 
-    // --- Call the separate decrypt + winner function ---
-    let result = decrypt_and_find_winner(&totals, &election.candidates, &client_key);
+    let Some(tally_bytes) = db.get(&format!("tallies:{}", election_id)) else {
+        return HttpResponse::InternalServerError().json(json!({ "error": "Tally missing" }));
+    };
+
+    let ballots = match election_ballots(&db, &election_id) {
+        Ok(ballots) => ballots,
+        Err(resp) => return resp,
+    };
+    let published_ballots: Vec<serde_json::Value> = ballots
+        .iter()
+        .map(|(ballot, bytes)| {
+            json!({
+                "ballot_id": ballot.ballot_id,
+                "hash": hex_sha256(bytes),
+                "ciphertext": general_purpose::STANDARD.encode(bytes)
+            })
+        })
+        .collect();
 
     HttpResponse::Ok().json(json!({
         "election_id": election_id,
-        "winner_label": result.0,
-        "winner_id": result.1,
-        "totals": result.2,
-        "status": "Winner decrypted successfully"
+        "candidates": election.candidates,
+        "ballots": published_ballots,
+        "encrypted_tally": general_purpose::STANDARD.encode(&tally_bytes),
+        "aggregate_hash": hex_sha256(&tally_bytes),
+        "status": "Awaiting trustee decryption"
     }))
 }
 
-/// Separate function that decrypts tallies and finds the winner
-fn decrypt_and_find_winner(
-    totals: &Vec<FheUint8>,
-    candidates: &Vec<crate::models::Candidate>,
-    client_key: &ClientKey,
-) -> (String, u32, Vec<(String, u8)>) {
-    let mut plain_totals = Vec::new();
-    for (i, ct) in totals.iter().enumerate() {
-        let count: u8 = ct.decrypt(client_key);
-        plain_totals.push((candidates[i].label.clone(), count));
+/// Publishes the result the trustees decrypted with the `tally` tool, once its
+/// totals and rejected count account for every stored ballot. Nothing proves
+/// they are the aggregate's decryption; that rests on the trustees.
+#[post("/admin/elections/{id}/result")]
+async fn publish_result(
+    db: web::Data<Database>,
+    path: web::Path<String>,
+    body: web::Json<serde_json::Value>,
+) -> HttpResponse {
+    let election_id = path.into_inner();
+    let mut election = match load_election(&db, &election_id) {
+        Ok(election) => election,
+        Err(resp) => return resp,
+    };
+    if election.state != ElectionState::Tallied {
+        return HttpResponse::Conflict().json(json!({
+            "error": format!("Election is {:?}; only tallied elections can publish", election.state)
+        }));
+    }
+
+    let totals: Vec<(String, u32)> = match serde_json::from_value(body["totals"].clone()) {
+        Ok(totals) => totals,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(json!({ "error": "Missing or bad totals" }));
+        }
+    };
+    let Some(rejected) = body["rejected_ballots"].as_u64() else {
+        return HttpResponse::BadRequest().json(json!({ "error": "Missing rejected_ballots" }));
+    };
+    let Some(tally_bytes) = db.get(&format!("tallies:{}", election_id)) else {
+        return HttpResponse::InternalServerError().json(json!({ "error": "Tally missing" }));
+    };
+
+    // Every stored ballot is either counted once or rejected
+    let ballots = match election_ballots(&db, &election_id) {
+        Ok(ballots) => ballots,
+        Err(resp) => return resp,
+    };
+    let accounted = totals
+        .iter()
+        .try_fold(rejected, |acc, (_, count)| acc.checked_add(*count as u64));
+    if body["election_id"].as_str() != Some(election_id.as_str())
+        || totals.len() != election.candidates.len()
+        || accounted != Some(ballots.len() as u64)
+    {
+        return HttpResponse::BadRequest()
+            .json(json!({ "error": "Result does not match this election" }));
     }
 
-    let (winner_label, _winner_votes) =
-        plain_totals.iter().max_by_key(|(_, count)| *count).unwrap();
+    let ballot_hashes: Vec<String> = ballots.iter().map(|(_, bytes)| hex_sha256(bytes)).collect();
+    let mut result = body.into_inner();
+    result["ballot_hashes"] = json!(ballot_hashes);
+    result["encrypted_tally"] = json!(general_purpose::STANDARD.encode(&tally_bytes));
+    db.put(
+        &format!("results:{}", election_id),
+        &serde_json::to_vec(&result).unwrap(),
+    );
+    election.transition(ElectionState::Published).unwrap();
+    save_election(&db, &election);
+    HttpResponse::Ok().json(json!({ "status": election.state }))
+}
+
+/// The election's ballots in bulletin order, each with its serialized ciphertexts.
+///
+/// Fails if a ballot on the bulletin board is missing or no longer matches its
+/// entry, so a tampered ballot store is never tallied.
+fn election_ballots(
+    db: &Database,
+    election_id: &str,
+) -> Result<Vec<(Ballot, Vec<u8>)>, HttpResponse> {
+    let head = bulletin::head(db, election_id);
+    let entries = bulletin::entries(db, election_id, 0, head.length);
+    if !bulletin::verify_chain(&entries, &head) {
+        return Err(HttpResponse::InternalServerError()
+            .json(json!({ "error": "Bulletin board does not replay to its head" })));
+    }
 
-    let winner_id = candidates
+    entries
         .iter()
-        .find(|c| c.label == *winner_label)
-        .map(|c| c.id)
-        .unwrap_or(0);
+        .map(|entry| {
+            let ballot = db
+                .get(&format!("ballots:{}", entry.ballot_id))
+                .and_then(|bytes| bincode::deserialize::<Ballot>(&bytes).ok())
+                .filter(|ballot| ballot.election_id == election_id);
+            let Some(ballot) = ballot else {
+                return Err(HttpResponse::InternalServerError().json(json!({
+                    "error": format!("Ballot {} on the bulletin board is missing", entry.ballot_id)
+                })));
+            };
+            let bytes = bincode::serialize(&ballot.encrypted_vector).unwrap();
+            if hex_sha256(&bytes) != entry.ciphertext_hash {
+                return Err(HttpResponse::InternalServerError().json(json!({
+                    "error": format!("Ballot {} does not match the bulletin board", entry.ballot_id)
+                })));
+            }
+            Ok((ballot, bytes))
+        })
+        .collect()
+}
 
-    (winner_label.clone(), winner_id, plain_totals)
+fn hex_sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
 }
 
 pub fn routes() -> Scope {
     web::scope("")
         .service(create_election)
+        .service(open_election)
         .service(close_election)
+        .service(tally_election)
         .service(list_elections)
         .service(get_election)
-        .service(submit_ballot)
+        .service(get_public_key)
+        .service(submit_encrypted_ballot)
+        .service(get_ballot_proof)
+        .service(get_trustee_share)
+        .service(get_bulletin)
         .service(calculate_winner)
+        .service(publish_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulletin::{BulletinEntry, SignedHead};
+    use actix_web::{App, http::StatusCode, test};
+    use ed25519_dalek::SigningKey;
+    use homomorphic::custody::{KeyShare, combine_client_key, trustee_keypair};
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("vote_db_test_{}", Uuid::new_v4()));
+        Database::init(path.to_str().unwrap())
+    }
+
+    fn insert_election(
+        db: &Database,
+        state: ElectionState,
+        start_time: u64,
+        end_time: u64,
+    ) -> String {
+        let election = Election {
+            id: Uuid::new_v4().to_string(),
+            name: "Test".to_string(),
+            start_time,
+            end_time,
+            candidates: vec![
+                Candidate {
+                    id: 1,
+                    label: "A".to_string(),
+                },
+                Candidate {
+                    id: 2,
+                    label: "B".to_string(),
+                },
+            ],
+            closed: false,
+            state,
+        };
+        save_election(db, &election);
+        election.id
+    }
+
+    #[actix_web::test]
+    async fn test_lifecycle_transitions_only_move_forward() {
+        let db = test_db();
+        let id = insert_election(&db, ElectionState::Draft, 0, u64::MAX);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .service(routes()),
+        )
+        .await;
+
+        let post = |uri: String| test::TestRequest::post().uri(&uri).to_request();
+
+        // Cannot close or tally a draft
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/close", id))).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/tally", id))).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/open", id))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/open", id))).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        // An open election has no result and cannot be tallied yet
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/elections/{}/result", id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/tally", id))).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let resp = test::call_service(&app, post(format!("/admin/elections/{}/close", id))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let election = load_election(&db, &id).unwrap();
+        assert_eq!(election.state, ElectionState::Closed);
+        assert!(election.closed);
+
+        // Publishing needs a tally first
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!("/admin/elections/{}/result", id))
+                .set_json(json!({ "election_id": id }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    async fn test_ballots_rejected_outside_voting_window() {
+        let db = test_db();
+        let now = now();
+        let draft = insert_election(&db, ElectionState::Draft, 0, u64::MAX);
+        let closed = insert_election(&db, ElectionState::Closed, 0, u64::MAX);
+        let not_started = insert_election(&db, ElectionState::Open, now + 3600, now + 7200);
+        let ended = insert_election(&db, ElectionState::Open, 0, now);
+        let running = insert_election(&db, ElectionState::Open, 0, u64::MAX);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .service(routes()),
+        )
+        .await;
+
+        let vote = |id: &str, path: &str| {
+            test::TestRequest::post()
+                .uri(&format!("/elections/{}/{}", id, path))
+                .set_json(json!({ "token": "t", "encrypted_ballot": "" }))
+                .to_request()
+        };
+
+        for id in [&draft, &closed, &not_started, &ended] {
+            let resp = test::call_service(&app, vote(id, "ballots/encrypted")).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", id);
+        }
+
+        // Inside the window the request gets past the lifecycle check
+        let resp = test::call_service(&app, vote(&running, "ballots/encrypted")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // There is no plaintext ballot path for the server to see the vote on
+        let resp = test::call_service(&app, vote(&running, "ballots")).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_bulletin_pages_signed_head_and_receipts() {
+        let db = test_db();
+        let id = insert_election(&db, ElectionState::Open, 0, u64::MAX);
+        let ballot_ids: Vec<String> = (0..5)
+            .map(|i| store_ballot(&db, &id, Vec::new(), format!("token-{}", i)))
+            .collect();
+
+        let signer = BulletinSigner::new(SigningKey::generate(&mut rand::rngs::OsRng));
+        let public_key = signer.public_key();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .app_data(web::Data::new(signer))
+                .service(routes()),
+        )
+        .await;
+
+        let mut replayed: Vec<BulletinEntry> = vec![];
+        for offset in [0, 2, 4] {
+            let page: serde_json::Value = test::call_and_read_body_json(
+                &app,
+                test::TestRequest::get()
+                    .uri(&format!(
+                        "/elections/{}/bulletin?offset={}&limit=2",
+                        id, offset
+                    ))
+                    .to_request(),
+            )
+            .await;
+            replayed.extend(
+                serde_json::from_value::<Vec<BulletinEntry>>(page["entries"].clone()).unwrap(),
+            );
+
+            let head: SignedHead = serde_json::from_value(page["head"].clone()).unwrap();
+            assert!(head.verify(&public_key));
+            assert_eq!(head.head.length, 5);
+        }
+
+        let head = bulletin::head(&db, &id);
+        assert!(bulletin::verify_chain(&replayed, &head));
+        let order: Vec<&String> = replayed.iter().map(|e| &e.ballot_id).collect();
+        assert_eq!(order, ballot_ids.iter().collect::<Vec<_>>());
+        assert_eq!(election_ballots(&db, &id).unwrap().len(), 5);
+
+        // Every receipt proves its ballot under the root a tally would commit to
+        let ballot_hashes: Vec<[u8; 32]> = replayed
+            .iter()
+            .map(|e| zk::merkle::decode_hash(&e.ciphertext_hash).unwrap())
+            .collect();
+        let tally_root = hex::encode(zk::merkle::root(&ballot_hashes));
+        for ballot_id in &ballot_ids {
+            let body: serde_json::Value = test::call_and_read_body_json(
+                &app,
+                test::TestRequest::get()
+                    .uri(&format!("/elections/{}/ballots/{}/proof", id, ballot_id))
+                    .to_request(),
+            )
+            .await;
+            let receipt: InclusionReceipt =
+                serde_json::from_value(body["receipt"].clone()).unwrap();
+            assert!(receipt.verify().unwrap());
+            assert_eq!(receipt.root, tally_root);
+        }
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/elections/{}/ballots/unknown/proof", id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // A ballot deleted from the store no longer reaches the tally
+        db.db.delete(format!("ballots:{}", ballot_ids[2])).unwrap();
+        assert!(election_ballots(&db, &id).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_each_trustee_fetches_only_their_sealed_share() {
+        let db = test_db();
+        let id = insert_election(&db, ElectionState::Draft, 0, u64::MAX);
+        let keys: Vec<([u8; 32], [u8; 32])> = (0..3).map(|_| trustee_keypair()).collect();
+        let body = json!({
+            "trustees": keys
+                .iter()
+                .enumerate()
+                .map(|(i, (_, public))| json!({
+                    "name": format!("trustee-{}", i),
+                    "public_key": hex::encode(public)
+                }))
+                .collect::<Vec<_>>()
+        });
+        let (trustees, threshold) = parse_trustees(&body).unwrap();
+        assert_eq!(threshold, 2);
+
+        let (client_key, _) = generate_keys(ConfigBuilder::default().build());
+        seal_trustee_shares(&db, &id, &client_key, &trustees, threshold).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .service(routes()),
+        )
+        .await;
+
+        let urls = share_urls(&id, &trustees);
+        let mut opened = vec![];
+        for (i, url) in urls.iter().enumerate() {
+            let body: serde_json::Value = test::call_and_read_body_json(
+                &app,
+                test::TestRequest::get()
+                    .uri(url["share_url"].as_str().unwrap())
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(body["trustee"], format!("trustee-{}", i));
+            let sealed = general_purpose::STANDARD
+                .decode(body["sealed_share"].as_str().unwrap())
+                .unwrap();
+
+            // Only the named trustee's secret opens it
+            let other = &keys[(i + 1) % keys.len()].0;
+            assert!(KeyShare::open(&sealed, other).is_err());
+            opened.push(KeyShare::open(&sealed, &keys[i].0).unwrap());
+        }
+        assert!(combine_client_key(&opened[1..]).is_ok());
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/elections/{}/trustees/3/share", id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_trustees_need_distinct_names_and_valid_keys() {
+        let (_, public) = trustee_keypair();
+        let trustee = |name: &str, key: &str| json!({ "name": name, "public_key": key });
+        let key = hex::encode(public);
+
+        assert!(parse_trustees(&json!({})).is_err());
+        assert!(parse_trustees(&json!({ "trustees": ["alice", "bob"] })).is_err());
+        assert!(parse_trustees(&json!({ "trustees": [trustee("a", "beef")] })).is_err());
+        assert!(
+            parse_trustees(&json!({ "trustees": [trustee("a", &key), trustee("a", &key)] }))
+                .is_err()
+        );
+        assert!(
+            parse_trustees(&json!({ "trustees": [trustee("a", &key)], "threshold": 2 })).is_err()
+        );
+        assert!(parse_trustees(&json!({ "trustees": [trustee("a", &key)] })).is_ok());
+    }
 }
@@ -1,12 +1,17 @@
 #[allow(deprecated)]
 use crate::db::Database;
+use crate::routes::election::{parse_trustees, seal_trustee_shares, share_urls};
 use actix_web::{HttpResponse, Scope, post, web};
 use serde_json::json;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tfhe::{ConfigBuilder, generate_keys};
 
 #[post("/{id}/keys")]
-async fn generate_election_keys(db: web::Data<Database>, path: web::Path<String>) -> HttpResponse {
+async fn generate_election_keys(
+    db: web::Data<Database>,
+    path: web::Path<String>,
+    body: web::Json<serde_json::Value>,
+) -> HttpResponse {
     let election_id = path.into_inner();
 
     let key_path = format!("keys:{}", election_id);
@@ -14,17 +19,27 @@ async fn generate_election_keys(db: web::Data<Database>, path: web::Path<String>
         return HttpResponse::Conflict().json(json!({ "error": "Keys already exist" }));
     }
 
+    let (trustees, threshold) = match parse_trustees(&body) {
+        Ok(t) => t,
+        Err(resp) => return resp,
+    };
+
     let config = ConfigBuilder::default().build();
     let (client_key, server_key) = generate_keys(config);
 
-    let client_bytes = bincode::serialize(&client_key).unwrap();
+    // Only the server key is stored; the client key goes out as sealed trustee shares
+    if let Err(resp) = seal_trustee_shares(&db, &election_id, &client_key, &trustees, threshold) {
+        return resp;
+    }
+    drop(client_key);
+
     let server_bytes = bincode::serialize(&server_key).unwrap();
-    let client_b64 = base64::encode(client_bytes);
     let server_b64 = base64::encode(server_bytes);
 
     let record = json!({
-        "client_key": client_b64,
         "server_key": server_b64,
+        "trustees": trustees,
+        "threshold": threshold,
         "created_at": SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -35,7 +50,9 @@ async fn generate_election_keys(db: web::Data<Database>, path: web::Path<String>
 
     HttpResponse::Ok().json(json!({
         "election_id": election_id,
-        "server_key": server_b64
+        "server_key": server_b64,
+        "threshold": threshold,
+        "trustee_shares": share_urls(&election_id, &trustees)
     }))
 }
 
@@ -0,0 +1,279 @@
+//! Offline tally tool run by the election trustees, and its audit counterpart.
+//!
+//! The server only ever holds the server key and the public encryption key, so it
+//! can add ballots up but not read the totals. At tally time, `threshold` trustees
+//! bring their key shares here, the client key is rebuilt in memory, and the
+//! encrypted tally published at `GET /elections/{id}/result` is decrypted. Only the
+//! aggregate and the rejected count are decrypted; individual ballots are never
+//! opened.
+//!
+//! Usage:
+//! - `tally trustee-key <secret key file>` makes a trustee's key pair, writes the
+//!   secret key to the file and prints the public key to register as the trustee's
+//!   `public_key` when the election is created.
+//! - `tally open-share <sealed.json> <secret key file>` opens the body of
+//!   `GET /elections/{id}/trustees/{index}/share` and prints the base64 share.
+//! - `tally <result.json> <share file>...`, where each share file holds a base64
+//!   share opened as above. Prints the result as JSON, ready for
+//!   `POST /admin/elections/{id}/result`.
+//! - `tally audit <published.json> [receipt.json]` checks that a published result
+//!   accounts for every counted ballot and, given a voter's receipt (the body of
+//!   `GET /elections/{id}/ballots/{ballot_id}/proof`), that their ballot is in the
+//!   counted ballot set. Neither shows the totals are the aggregate's decryption.
+
+use base64::{Engine as _, engine::general_purpose};
+use homomorphic::FheDecrypt;
+use homomorphic::ballot::EncryptedTally;
+use homomorphic::custody::{KeyShare, combine_client_key, trustee_keypair};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, process};
+use tfhe::ClientKey;
+use zk::merkle::{self, InclusionReceipt};
+
+#[derive(Deserialize)]
+struct Candidate {
+    id: u32,
+    name: String,
+}
+
+/// Body of `GET /elections/{id}/result` before the result is published.
+#[derive(Deserialize)]
+struct EncryptedResult {
+    election_id: String,
+    candidates: Vec<Candidate>,
+    ballots: Vec<serde::de::IgnoredAny>,
+    encrypted_tally: String,
+}
+
+/// Decrypted totals and the winner they give.
+#[derive(Serialize, Deserialize)]
+struct Decrypted {
+    winner_label: String,
+    winner_id: u32,
+    totals: Vec<(String, u32)>,
+    rejected_ballots: u32,
+}
+
+/// Decrypted result, in the shape `POST /admin/elections/{id}/result` expects.
+#[derive(Serialize)]
+struct TallyResult {
+    election_id: String,
+    #[serde(flatten)]
+    decrypted: Decrypted,
+}
+
+/// Body of `GET /elections/{id}/ballots/{ballot_id}/proof`.
+#[derive(Deserialize)]
+struct BallotProof {
+    receipt: InclusionReceipt,
+}
+
+/// Body of `GET /elections/{id}/trustees/{index}/share`.
+#[derive(Deserialize)]
+struct FetchedShare {
+    sealed_share: String,
+}
+
+/// Body of `GET /elections/{id}/result` once the result is published.
+#[derive(Deserialize)]
+struct PublishedResult {
+    #[serde(flatten)]
+    decrypted: Decrypted,
+    /// SHA-256 of each counted ballot, in tally order.
+    ballot_hashes: Vec<String>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let outcome = match args.get(1).map(String::as_str) {
+        Some("audit") if args.len() == 3 => audit(&args[2], None),
+        Some("audit") if args.len() == 4 => audit(&args[2], Some(&args[3])),
+        Some("trustee-key") if args.len() == 3 => trustee_key(&args[2]),
+        Some("open-share") if args.len() == 4 => open_share(&args[2], &args[3]),
+        Some(_) if args.len() >= 3 => run(&args[1], &args[2..]),
+        _ => {
+            eprintln!("Usage: tally trustee-key <secret key file>");
+            eprintln!("       tally open-share <sealed.json> <secret key file>");
+            eprintln!("       tally <result.json> <share file>...");
+            eprintln!("       tally audit <published.json> [receipt.json]");
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = outcome {
+        eprintln!("tally: {}", e);
+        process::exit(1);
+    }
+}
+
+fn trustee_key(secret_path: &str) -> Result<(), String> {
+    if fs::metadata(secret_path).is_ok() {
+        return Err(format!("{} already exists", secret_path));
+    }
+
+    let (secret, public) = trustee_keypair();
+    fs::write(secret_path, hex::encode(secret))
+        .map_err(|e| format!("cannot write {}: {}", secret_path, e))?;
+    println!("{}", hex::encode(public));
+
+    Ok(())
+}
+
+fn open_share(sealed_path: &str, secret_path: &str) -> Result<(), String> {
+    let fetched: FetchedShare = read_json(sealed_path)?;
+    let sealed = decode_base64(&fetched.sealed_share, "sealed_share")?;
+
+    let text = fs::read_to_string(secret_path)
+        .map_err(|e| format!("cannot read {}: {}", secret_path, e))?;
+    let secret: [u8; 32] = hex::decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("{} is not a trustee secret key", secret_path))?;
+
+    let share = KeyShare::open(&sealed, &secret).map_err(|e| e.to_string())?;
+    let bytes = share.to_bytes().map_err(|e| e.to_string())?;
+    println!("{}", general_purpose::STANDARD.encode(bytes));
+
+    Ok(())
+}
+
+fn run(result_path: &str, share_paths: &[String]) -> Result<(), String> {
+    let result: EncryptedResult = read_json(result_path)?;
+
+    let shares = share_paths
+        .iter()
+        .map(|path| read_share(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let client_key = combine_client_key(&shares).map_err(|e| e.to_string())?;
+
+    let tally_bytes = decode_base64(&result.encrypted_tally, "encrypted_tally")?;
+    let tally: EncryptedTally =
+        bincode::deserialize(&tally_bytes).map_err(|e| format!("bad encrypted tally: {}", e))?;
+    if tally.totals.len() != result.candidates.len() {
+        return Err(format!(
+            "tally has {} totals but the election has {} candidates",
+            tally.totals.len(),
+            result.candidates.len()
+        ));
+    }
+
+    let decrypted = decrypt_and_find_winner(&tally, &result.candidates, &client_key)?;
+
+    let accounted = accounted(&decrypted);
+    if accounted != result.ballots.len() as u64 {
+        return Err(format!(
+            "decrypted tally accounts for {} ballots but {} were published",
+            accounted,
+            result.ballots.len()
+        ));
+    }
+
+    let output = TallyResult {
+        election_id: result.election_id,
+        decrypted,
+    };
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+
+    Ok(())
+}
+
+/// Ballots a result accounts for: counted for a candidate or rejected.
+fn accounted(decrypted: &Decrypted) -> u64 {
+    decrypted.totals.iter().map(|(_, c)| *c as u64).sum::<u64>() + decrypted.rejected_ballots as u64
+}
+
+/// Checks a published result the way any auditor would, and optionally a
+/// voter's inclusion receipt against it.
+fn audit(published_path: &str, receipt_path: Option<&str>) -> Result<(), String> {
+    let published: PublishedResult = read_json(published_path)?;
+
+    if accounted(&published.decrypted) != published.ballot_hashes.len() as u64 {
+        return Err(format!(
+            "result accounts for {} ballots but {} were counted",
+            accounted(&published.decrypted),
+            published.ballot_hashes.len()
+        ));
+    }
+    println!("Result accounts for every counted ballot");
+
+    if let Some(path) = receipt_path {
+        let receipt = read_json::<BallotProof>(path)?.receipt;
+        check_receipt(&receipt, &published.ballot_hashes)?;
+        println!("Ballot {} is in the counted ballot set", receipt.ballot_id);
+    }
+
+    Ok(())
+}
+
+/// The receipt must verify, and its root and leaf must be those of the
+/// counted ballots.
+fn check_receipt(receipt: &InclusionReceipt, ballot_hashes: &[String]) -> Result<(), String> {
+    if !receipt.verify().map_err(|e| e.to_string())? {
+        return Err("receipt does not verify".to_string());
+    }
+
+    let leaves = ballot_hashes
+        .iter()
+        .map(|h| merkle::decode_hash(h))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let root = hex::encode(merkle::root(&leaves));
+    if receipt.root != root {
+        return Err(
+            "receipt is for another ballot set; fetch it again once the election is closed"
+                .to_string(),
+        );
+    }
+    if ballot_hashes.get(receipt.index as usize) != Some(&receipt.leaf_hash) {
+        return Err("ballot is not in the counted ballot set".to_string());
+    }
+
+    Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &str) -> Result<T, String> {
+    let bytes = fs::read(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("{} is not the expected JSON: {}", path, e))
+}
+
+fn decode_base64(text: &str, what: &str) -> Result<Vec<u8>, String> {
+    general_purpose::STANDARD
+        .decode(text)
+        .map_err(|_| format!("{} is not valid base64", what))
+}
+
+fn read_share(path: &str) -> Result<KeyShare, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let bytes = general_purpose::STANDARD
+        .decode(text.trim())
+        .map_err(|_| format!("{} is not a base64 key share", path))?;
+    KeyShare::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Decrypts the totals and rejected count, and picks the winner.
+fn decrypt_and_find_winner(
+    tally: &EncryptedTally,
+    candidates: &[Candidate],
+    client_key: &ClientKey,
+) -> Result<Decrypted, String> {
+    let plain_totals: Vec<(String, u32)> = tally
+        .totals
+        .iter()
+        .zip(candidates)
+        .map(|(ct, c)| (c.name.clone(), ct.decrypt(client_key)))
+        .collect();
+
+    let (winner_index, _) = plain_totals
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (_, count))| *count)
+        .ok_or("election has no candidates")?;
+    let winner = &candidates[winner_index];
+
+    Ok(Decrypted {
+        winner_label: winner.name.clone(),
+        winner_id: winner.id,
+        totals: plain_totals,
+        rejected_ballots: tally.rejected.decrypt(client_key),
+    })
+}